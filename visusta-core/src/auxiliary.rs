@@ -0,0 +1,92 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use image::{ImageResult, RgbaImage};
+
+// How an auxiliary image is fitted to the size of the image the step reading it works on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizePolicy {
+    // Resampled to cover the image exactly, ignoring aspect ratio
+    Stretch,
+    // Repeated from the top left at its own size
+    Tile,
+    // Placed at its own size in the middle, cropped or padded with transparency
+    Centre,
+}
+
+// A named extra input a pipeline declares, such as a paper texture or a watermark
+#[derive(Debug, Clone)]
+pub struct AuxiliaryImage {
+    pub image: Arc<RgbaImage>,
+    pub fit: SizePolicy,
+}
+
+impl AuxiliaryImage {
+    pub fn create(image: RgbaImage) -> AuxiliaryImage {
+        assert!(
+            image.width() > 0 && image.height() > 0,
+            "Auxiliary image must not be empty"
+        );
+        AuxiliaryImage {
+            image: Arc::new(image),
+            fit: SizePolicy::Stretch,
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> ImageResult<AuxiliaryImage> {
+        Ok(AuxiliaryImage::create(image::open(path)?.to_rgba8()))
+    }
+
+    pub fn fit(mut self, fit: SizePolicy) -> AuxiliaryImage {
+        self.fit = fit;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlendMode {
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+}
+
+impl BlendMode {
+    // Channels in 0 - 1, `base` is the layer's image and `top` the second input
+    pub fn blend(&self, base: f32, top: f32) -> f32 {
+        match self {
+            BlendMode::Normal => top,
+            BlendMode::Multiply => base * top,
+            BlendMode::Screen => 1.0 - (1.0 - base) * (1.0 - top),
+            BlendMode::Overlay => {
+                if base < 0.5 {
+                    2.0 * base * top
+                } else {
+                    1.0 - 2.0 * (1.0 - base) * (1.0 - top)
+                }
+            }
+        }
+    }
+}
+
+// The second input is drawn over the layer's image
+#[derive(Debug, Clone, Copy)]
+pub struct BlendData {
+    pub mode: BlendMode,
+    pub opacity: f32,
+}
+
+impl BlendData {
+    pub fn create(mode: BlendMode) -> BlendData {
+        BlendData { mode, opacity: 1.0 }
+    }
+
+    pub fn opacity(mut self, opacity: f32) -> BlendData {
+        assert!(
+            (0.0..=1.0).contains(&opacity),
+            "Opacity must be between 0 and 1"
+        );
+        self.opacity = opacity;
+        self
+    }
+}
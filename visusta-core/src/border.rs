@@ -0,0 +1,85 @@
+// How a kernel reads positions past the edge of the image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BorderMode {
+    // Repeats the edge pixel
+    Clamp,
+    // Mirrors about the edge pixel without repeating it
+    Reflect,
+    // Reads from the opposite edge
+    Wrap,
+    // Reads a fixed luminance in 0 - 255
+    Constant(f32),
+}
+
+impl BorderMode {
+    // In bounds index for a position along an axis of the given size,
+    //  None when the constant should be read instead
+    pub fn index(&self, pos: isize, size: usize) -> Option<usize> {
+        let size = size as isize;
+        if (0..size).contains(&pos) {
+            return Some(pos as usize);
+        }
+
+        match self {
+            BorderMode::Clamp => Some(pos.clamp(0, size - 1) as usize),
+            BorderMode::Reflect => {
+                let last = size - 1;
+                if last == 0 {
+                    return Some(0);
+                }
+
+                let period = 2 * last;
+                let wrapped = pos.rem_euclid(period);
+                Some(if wrapped > last {
+                    (period - wrapped) as usize
+                } else {
+                    wrapped as usize
+                })
+            }
+            BorderMode::Wrap => Some(pos.rem_euclid(size) as usize),
+            BorderMode::Constant(_) => None,
+        }
+    }
+
+    pub fn constant(&self) -> f32 {
+        match self {
+            BorderMode::Constant(val) => *val,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BorderMode;
+
+    #[test]
+    fn in_bounds_positions_are_unchanged() {
+        for mode in [
+            BorderMode::Clamp,
+            BorderMode::Reflect,
+            BorderMode::Wrap,
+            BorderMode::Constant(0.0),
+        ] {
+            assert_eq!(mode.index(0, 5), Some(0));
+            assert_eq!(mode.index(4, 5), Some(4));
+        }
+    }
+
+    #[test]
+    fn out_of_bounds_positions_map_per_mode() {
+        assert_eq!(BorderMode::Clamp.index(-2, 5), Some(0));
+        assert_eq!(BorderMode::Clamp.index(6, 5), Some(4));
+
+        assert_eq!(BorderMode::Reflect.index(-1, 5), Some(1));
+        assert_eq!(BorderMode::Reflect.index(-2, 5), Some(2));
+        assert_eq!(BorderMode::Reflect.index(5, 5), Some(3));
+        assert_eq!(BorderMode::Reflect.index(-3, 1), Some(0));
+
+        assert_eq!(BorderMode::Wrap.index(-1, 5), Some(4));
+        assert_eq!(BorderMode::Wrap.index(6, 5), Some(1));
+
+        assert_eq!(BorderMode::Constant(7.0).index(-1, 5), None);
+        assert_eq!(BorderMode::Constant(7.0).constant(), 7.0);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CannyData {
+    pub sigma: f32,
+    pub thresholds: CannyThresholds,
+    pub border: BorderMode,
+}
+
+// Thresholds are on the gradient magnitude scaled to 0 - 255
+#[derive(Debug, Clone, Copy)]
+pub enum CannyThresholds {
+    Manual { low: f32, high: f32 },
+    // High threshold from Otsu on the suppressed magnitudes, low is half of it
+    Otsu,
+}
+
+impl CannyData {
+    pub fn create() -> CannyData {
+        CannyData {
+            sigma: 1.4,
+            thresholds: CannyThresholds::Otsu,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn sigma(mut self, sigma: f32) -> CannyData {
+        assert!(sigma > 0.0, "Sigma must be positive");
+        self.sigma = sigma;
+        self
+    }
+
+    pub fn thresholds(mut self, low: f32, high: f32) -> CannyData {
+        assert!(low <= high, "Low threshold must not exceed high threshold");
+        self.thresholds = CannyThresholds::Manual { low, high };
+        self
+    }
+
+    pub fn otsu(mut self) -> CannyData {
+        self.thresholds = CannyThresholds::Otsu;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> CannyData {
+        self.border = border;
+        self
+    }
+}
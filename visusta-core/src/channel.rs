@@ -0,0 +1,301 @@
+use std::f32::consts::TAU;
+use std::sync::Arc;
+
+use crate::colour::hsv_to_rgb;
+use crate::gradient_map::GradientMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RgbaChannel {
+    Red,
+    Green,
+    Blue,
+    Alpha,
+}
+
+// Where an output channel reads its value from, all on a 0 - 255 scale before mapping
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChannelSource {
+    // Normalised gradient magnitude, using the step's magnitude measure
+    Magnitude,
+    // Signed gradients in Sobel 3x3 units
+    Gx,
+    Gy,
+    // Gradient angle as a hue position, a full turn spans 0 - 255
+    Angle,
+    // The step's continuous response, the DoG value or the gradient magnitude
+    Response,
+    // Luminance of the step's input
+    Luminance,
+    // A channel of the step's original image, grey from luminance when it has none
+    Original(RgbaChannel),
+    Constant(f32),
+}
+
+// Per-pixel values a step can offer, sources it doesn't have read as 0
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChannelSample {
+    pub magnitude: f32,
+    pub gx: f32,
+    pub gy: f32,
+    pub response: f32,
+    pub luminance: f32,
+    pub original: [f32; 4],
+}
+
+impl ChannelSample {
+    pub fn value(&self, source: ChannelSource) -> f32 {
+        match source {
+            ChannelSource::Magnitude => self.magnitude,
+            ChannelSource::Gx => self.gx,
+            ChannelSource::Gy => self.gy,
+            ChannelSource::Angle => {
+                if self.gx == 0.0 && self.gy == 0.0 {
+                    0.0
+                } else {
+                    self.gy.atan2(self.gx).rem_euclid(TAU) / TAU * 255.0
+                }
+            }
+            ChannelSource::Response => self.response,
+            ChannelSource::Luminance => self.luminance,
+            ChannelSource::Original(channel) => match channel {
+                RgbaChannel::Red => self.original[0],
+                RgbaChannel::Green => self.original[1],
+                RgbaChannel::Blue => self.original[2],
+                RgbaChannel::Alpha => self.original[3],
+            },
+            ChannelSource::Constant(val) => val,
+        }
+    }
+}
+
+// source -> abs -> scale and offset -> clamp -> lut
+#[derive(Debug, Clone)]
+pub struct ChannelMap {
+    pub source: ChannelSource,
+    pub scale: f32,
+    pub offset: f32,
+    pub abs: bool,
+    pub clamp: (f32, f32),
+    // Indexed by the clamped value, for curves and gradient maps
+    pub lut: Option<Arc<[u8; 256]>>,
+}
+
+impl ChannelMap {
+    pub fn create(source: ChannelSource) -> ChannelMap {
+        ChannelMap {
+            source,
+            scale: 1.0,
+            offset: 0.0,
+            abs: false,
+            clamp: (0.0, 255.0),
+            lut: None,
+        }
+    }
+
+    pub fn constant(val: u8) -> ChannelMap {
+        ChannelMap::create(ChannelSource::Constant(val as f32))
+    }
+
+    pub fn none() -> ChannelMap {
+        ChannelMap::constant(0)
+    }
+
+    pub fn scale(mut self, scale: f32) -> ChannelMap {
+        self.scale = scale;
+        self
+    }
+
+    pub fn offset(mut self, offset: f32) -> ChannelMap {
+        self.offset = offset;
+        self
+    }
+
+    pub fn abs(mut self) -> ChannelMap {
+        self.abs = true;
+        self
+    }
+
+    pub fn clamp(mut self, min: f32, max: f32) -> ChannelMap {
+        assert!(
+            (0.0..=255.0).contains(&min) && (0.0..=255.0).contains(&max) && min <= max,
+            "Clamp range must sit within 0 - 255"
+        );
+        self.clamp = (min, max);
+        self
+    }
+
+    pub fn lut(mut self, lut: Arc<[u8; 256]>) -> ChannelMap {
+        self.lut = Some(lut);
+        self
+    }
+
+    pub fn map(&self, sample: &ChannelSample) -> u8 {
+        let mut val = sample.value(self.source);
+
+        if self.abs {
+            val = val.abs();
+        }
+
+        let val = (val * self.scale + self.offset)
+            .clamp(self.clamp.0, self.clamp.1)
+            .round() as u8;
+
+        match self.lut.as_ref() {
+            Some(lut) => lut[val as usize],
+            None => val,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChannelMapping {
+    pub r: ChannelMap,
+    pub g: ChannelMap,
+    pub b: ChannelMap,
+    pub a: ChannelMap,
+}
+
+impl ChannelMapping {
+    // Flat colour wherever the step lets a pixel through
+    pub fn solid(r: u8, g: u8, b: u8, a: u8) -> ChannelMapping {
+        ChannelMapping {
+            r: ChannelMap::constant(r),
+            g: ChannelMap::constant(g),
+            b: ChannelMap::constant(b),
+            a: ChannelMap::constant(a),
+        }
+    }
+
+    // Every channel reads `source` and looks its colour up in the gradient map,
+    //  so a DoG response or magnitude is tinted smoothly
+    pub fn gradient_map(source: ChannelSource, map: &GradientMap) -> ChannelMapping {
+        let lut = map.build_lut();
+        let channel = |channel: usize| {
+            ChannelMap::create(source).lut(Arc::new(std::array::from_fn(|idx| lut[idx][channel])))
+        };
+
+        ChannelMapping {
+            r: channel(0),
+            g: channel(1),
+            b: channel(2),
+            a: channel(3),
+        }
+    }
+
+    pub fn map(&self, sample: &ChannelSample) -> [u8; 4] {
+        [
+            self.r.map(sample),
+            self.g.map(sample),
+            self.b.map(sample),
+            self.a.map(sample),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HueWrap {
+    // Opposite gradients share a hue, the wheel spans 180 degrees of edge orientation
+    Orientation,
+    // Every gradient direction has its own hue, the wheel spans 360 degrees
+    Direction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HueIntensity {
+    Full,
+    Magnitude,
+}
+
+// Gradient angle on the HSV hue wheel, magnitude driving value and/or alpha
+#[derive(Debug, Clone, Copy)]
+pub struct DirectionHue {
+    pub wrap: HueWrap,
+    // Degrees added to the hue, turns the wheel so a chosen direction lands on red
+    pub hue_offset: f32,
+    pub saturation: f32,
+    pub value: HueIntensity,
+    pub alpha: HueIntensity,
+    // Multiplies the normalised magnitude before it is clamped to 1
+    pub gain: f32,
+}
+
+impl DirectionHue {
+    pub fn create() -> DirectionHue {
+        DirectionHue {
+            wrap: HueWrap::Direction,
+            hue_offset: 0.0,
+            saturation: 1.0,
+            value: HueIntensity::Magnitude,
+            alpha: HueIntensity::Full,
+            gain: 1.0,
+        }
+    }
+
+    // Every edge pixel at full brightness, for reading directions off the image
+    pub fn analysis() -> DirectionHue {
+        DirectionHue::create().value(HueIntensity::Full)
+    }
+
+    // Bright saturated strokes that fade out with the edge, for compositing over dark layers
+    pub fn neon() -> DirectionHue {
+        DirectionHue::create()
+            .wrap(HueWrap::Orientation)
+            .alpha(HueIntensity::Magnitude)
+            .gain(2.5)
+    }
+
+    pub fn wrap(mut self, wrap: HueWrap) -> DirectionHue {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn hue_offset(mut self, hue_offset: f32) -> DirectionHue {
+        self.hue_offset = hue_offset;
+        self
+    }
+
+    pub fn saturation(mut self, saturation: f32) -> DirectionHue {
+        assert!(
+            (0.0..=1.0).contains(&saturation),
+            "Saturation must be between 0 and 1"
+        );
+        self.saturation = saturation;
+        self
+    }
+
+    pub fn value(mut self, value: HueIntensity) -> DirectionHue {
+        self.value = value;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: HueIntensity) -> DirectionHue {
+        self.alpha = alpha;
+        self
+    }
+
+    pub fn gain(mut self, gain: f32) -> DirectionHue {
+        assert!(gain > 0.0, "Gain must be positive");
+        self.gain = gain;
+        self
+    }
+
+    // `magnitude` is the normalised 0 - 255 value the step thresholds on
+    pub fn map(&self, gx: f32, gy: f32, magnitude: f32) -> [u8; 4] {
+        let degrees = gy.atan2(gx).to_degrees();
+
+        let hue = match self.wrap {
+            HueWrap::Orientation => degrees.rem_euclid(180.0) * 2.0,
+            HueWrap::Direction => degrees.rem_euclid(360.0),
+        } + self.hue_offset;
+
+        let strength = (magnitude / 255.0 * self.gain).clamp(0.0, 1.0);
+        let intensity = |mode: HueIntensity| match mode {
+            HueIntensity::Full => 1.0,
+            HueIntensity::Magnitude => strength,
+        };
+
+        let [r, g, b] = hsv_to_rgb([hue, self.saturation, intensity(self.value)]);
+
+        [r, g, b, (intensity(self.alpha) * 255.0).round() as u8]
+    }
+}
@@ -0,0 +1,144 @@
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy)]
+pub struct GrainData {
+    pub seed: u64,
+    // Standard deviation of the noise as a fraction of full range
+    pub strength: f32,
+    // 0 gives flat grain, 1 keeps grain to the midtones like film
+    pub luminance_response: f32,
+    pub chroma: bool,
+}
+
+impl GrainData {
+    pub fn create(seed: u64) -> GrainData {
+        GrainData {
+            seed,
+            strength: 0.08,
+            luminance_response: 0.75,
+            chroma: false,
+        }
+    }
+
+    pub fn strength(mut self, strength: f32) -> GrainData {
+        assert!(strength >= 0.0, "Strength must not be negative");
+        self.strength = strength;
+        self
+    }
+
+    pub fn luminance_response(mut self, luminance_response: f32) -> GrainData {
+        self.luminance_response = luminance_response.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn chroma(mut self, chroma: bool) -> GrainData {
+        self.chroma = chroma;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct VignetteData {
+    // Normalised distance the darkening starts at, 1.0 touches the edge midpoints
+    pub radius: f32,
+    pub softness: f32,
+    pub colour: [u8; 3],
+    pub strength: f32,
+}
+
+impl VignetteData {
+    pub fn create() -> VignetteData {
+        VignetteData {
+            radius: 0.75,
+            softness: 0.6,
+            colour: [0, 0, 0],
+            strength: 0.8,
+        }
+    }
+
+    pub fn radius(mut self, radius: f32) -> VignetteData {
+        assert!(radius >= 0.0, "Radius must not be negative");
+        self.radius = radius;
+        self
+    }
+
+    pub fn softness(mut self, softness: f32) -> VignetteData {
+        assert!(softness >= 0.0, "Softness must not be negative");
+        self.softness = softness;
+        self
+    }
+
+    pub fn colour(mut self, colour: [u8; 3]) -> VignetteData {
+        self.colour = colour;
+        self
+    }
+
+    pub fn strength(mut self, strength: f32) -> VignetteData {
+        self.strength = strength.clamp(0.0, 1.0);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BloomComposite {
+    Additive,
+    Screen,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BloomData {
+    // Luminance the glow starts from, 0 - 255
+    pub threshold: f32,
+    pub levels: u32,
+    pub sigma: f32,
+    pub intensity: f32,
+    pub composite: BloomComposite,
+    pub border: BorderMode,
+}
+
+impl BloomData {
+    pub fn create() -> BloomData {
+        BloomData {
+            threshold: 200.0,
+            levels: 5,
+            sigma: 2.0,
+            intensity: 1.0,
+            composite: BloomComposite::Screen,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn threshold(mut self, threshold: f32) -> BloomData {
+        self.threshold = threshold.clamp(0.0, 254.0);
+        self
+    }
+
+    // Each level halves the resolution before blurring, widening the glow
+    pub fn levels(mut self, levels: u32) -> BloomData {
+        assert!(levels > 0, "Bloom needs at least one level");
+        self.levels = levels;
+        self
+    }
+
+    pub fn sigma(mut self, sigma: f32) -> BloomData {
+        assert!(sigma > 0.0, "Sigma must be positive");
+        self.sigma = sigma;
+        self
+    }
+
+    pub fn intensity(mut self, intensity: f32) -> BloomData {
+        assert!(intensity >= 0.0, "Intensity must not be negative");
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn composite(mut self, composite: BloomComposite) -> BloomData {
+        self.composite = composite;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> BloomData {
+        self.border = border;
+        self
+    }
+}
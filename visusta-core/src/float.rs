@@ -0,0 +1,38 @@
+// How float values are brought back into 0 - 255, alpha is always clamped to 0.0 - 1.0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FloatRange {
+    // 0.0 - 1.0 maps to 0 - 255, anything outside is clipped
+    Clamp,
+    // The image's own min - max is stretched over 0 - 255
+    Normalise,
+    // -1.0 - 1.0 maps to 0 - 255 with zero at mid grey, for signed responses
+    Signed,
+}
+
+impl FloatRange {
+    // Low and high ends of the mapped range, Normalise reads them off the values
+    pub fn bounds(&self, values: impl Iterator<Item = f32>) -> (f32, f32) {
+        match self {
+            FloatRange::Clamp => (0.0, 1.0),
+            FloatRange::Signed => (-1.0, 1.0),
+            FloatRange::Normalise => {
+                let (low, high) = values
+                    .filter(|val| val.is_finite())
+                    .fold((f32::MAX, f32::MIN), |(low, high), val| {
+                        (low.min(val), high.max(val))
+                    });
+
+                if low >= high {
+                    // Flat or empty images fall back to the Clamp mapping
+                    (0.0, 1.0)
+                } else {
+                    (low, high)
+                }
+            }
+        }
+    }
+}
+
+pub fn float_to_u8(val: f32, (low, high): (f32, f32)) -> u8 {
+    (((val - low) / (high - low)).clamp(0.0, 1.0) * 255.0).round() as u8
+}
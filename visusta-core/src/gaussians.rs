@@ -1,26 +1,46 @@
-use std::f32::consts::PI;
-
-type GaussianKernel = [f32; 9];
+use crate::{border::BorderMode, threshold::Cutoff};
 
 #[derive(Debug, Clone)]
 pub struct GaussianKernelData {
-    pub kernel: GaussianKernel,
-    pub cutoff: Option<f32>,
+    pub kernel_a: Vec<f32>,
+    pub kernel_b: Vec<f32>,
+    pub scalar: f32,
+    pub cutoff: Option<Cutoff>,
+    pub border: BorderMode,
 }
 
-#[derive(Debug, Clone)]
-pub struct GaussianColorData {
-    pub r: GaussianColorItem,
-    pub g: GaussianColorItem,
-    pub b: GaussianColorItem,
-    pub a: GaussianColorItem,
+// How the continuous DoG response is written back into luminance
+#[derive(Debug, Clone, Copy)]
+pub enum DogOutput {
+    // Zero response sits at mid grey, scaled by the gain
+    Signed(f32),
+    // Absolute response scaled by the gain
+    Magnitude(f32),
+    // Negative responses are dropped, positive scaled by the gain
+    Positive(f32),
 }
 
+// A single blur, checked on construction so a bad sigma can't reach a running pipeline
 #[derive(Debug, Clone, Copy)]
-pub enum GaussianColorItem {
-    NormalScale(f32),
-    Absolute(u8),
-    None,
+pub struct BlurData {
+    pub sigma: f32,
+    pub border: BorderMode,
+}
+
+impl BlurData {
+    pub fn create(sigma: f32) -> BlurData {
+        assert!(sigma > 0.0, "Sigma must be positive");
+
+        BlurData {
+            sigma,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn border(mut self, border: BorderMode) -> BlurData {
+        self.border = border;
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -28,7 +48,8 @@ pub struct GaussianBuilder {
     sigma_a: f32,
     sigma_b: f32,
     scalar: Option<f32>,
-    cutoff: Option<f32>,
+    cutoff: Option<Cutoff>,
+    border: BorderMode,
 }
 
 impl GaussianBuilder {
@@ -41,6 +62,7 @@ impl GaussianBuilder {
             sigma_b,
             scalar: None,
             cutoff: None,
+            border: BorderMode::Clamp,
         }
     }
 
@@ -50,71 +72,45 @@ impl GaussianBuilder {
     }
 
     pub fn cutoff(mut self, cutoff: f32) -> GaussianBuilder {
-        self.cutoff = Some(cutoff);
+        self.cutoff = Some(Cutoff::Value(cutoff));
         self
     }
 
-    pub fn build_kernel(&self) -> GaussianKernelData {
-        let (gaussian_a, sum_a, gaussian_b, sum_b) = self.calculate_continuous_gaussians();
+    pub fn cutoff_otsu(mut self) -> GaussianBuilder {
+        self.cutoff = Some(Cutoff::Otsu);
+        self
+    }
 
-        let kernel = self.calculate_normalised_difference(gaussian_a, sum_a, gaussian_b, sum_b);
+    pub fn border(mut self, border: BorderMode) -> GaussianBuilder {
+        self.border = border;
+        self
+    }
 
+    // Both Gaussians are separable, so each is kept as a 1D kernel
+    //  sized from its own sigma and applied as a horizontal then vertical pass
+    pub fn build_kernel(&self) -> GaussianKernelData {
         GaussianKernelData {
-            kernel,
+            kernel_a: gaussian_kernel_1d(self.sigma_a),
+            kernel_b: gaussian_kernel_1d(self.sigma_b),
+            scalar: self.scalar.unwrap_or(1.0),
             cutoff: self.cutoff,
+            border: self.border,
         }
     }
+}
 
-    fn calculate_normalised_difference(
-        &self,
-        gaussian_a: GaussianKernel,
-        sum_a: f32,
-        gaussian_b: GaussianKernel,
-        sum_b: f32,
-    ) -> GaussianKernel {
-        let mut res = GaussianKernel::default();
-
-        if let Some(scalar) = self.scalar {
-            for idx in 0..9usize {
-                // res[idx] = ((1.0 + scalar) * (gaussian_a[idx] / sum_a))
-                //     - (scalar * (gaussian_b[idx] / sum_b));
-                res[idx] = (gaussian_a[idx] / sum_a) - (scalar * (gaussian_b[idx] / sum_b));
-            }
-        } else {
-            for idx in 0..9usize {
-                res[idx] = (gaussian_a[idx] / sum_a) - (gaussian_b[idx] / sum_b);
-            }
-        }
-
-        res
-    }
-
-    fn calculate_continuous_gaussians(&self) -> (GaussianKernel, f32, GaussianKernel, f32) {
-        let mut gaussian_a = GaussianKernel::default();
-        let mut gaussian_b = GaussianKernel::default();
-
-        let mut sum_a = 0.0f32;
-        let mut sum_b = 0.0f32;
-
-        for (iy, y) in (-1..=1).enumerate() {
-            for (ix, x) in (-1..=1).enumerate() {
-                let val_a = Self::calculate_continuous(self.sigma_a, x as f32, y as f32);
-                gaussian_a[ix + (3 * iy)] = val_a;
-                sum_a += val_a;
+// Normalised 1D kernel with radius ceil(3 sigma), covers ~99.7% of the mass
+pub fn gaussian_kernel_1d(sigma: f32) -> Vec<f32> {
+    assert!(sigma > 0.0, "Sigma must be positive");
 
-                let val_b = Self::calculate_continuous(self.sigma_b, x as f32, y as f32);
-                gaussian_b[ix + (3 * iy)] = val_b;
-                sum_b += val_b;
-            }
-        }
+    let radius = (3.0 * sigma).ceil() as i32;
 
-        (gaussian_a, sum_a, gaussian_b, sum_b)
-    }
+    let mut kernel: Vec<f32> = (-radius..=radius)
+        .map(|x| (-((x * x) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
 
-    fn calculate_continuous(sigma: f32, x: f32, y: f32) -> f32 {
-        let base = 2.0 * PI * sigma * sigma;
-        let exp_power = 0.0 - ((x * x + y * y) / (2.0 * sigma * sigma));
+    let sum: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|val| *val /= sum);
 
-        exp_power.exp() / base
-    }
+    kernel
 }
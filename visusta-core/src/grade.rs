@@ -0,0 +1,192 @@
+use crate::colour::{decode_srgb, encode_srgb};
+
+// All adjustments take and return 0 - 1 encoded RGB, clamping is left to the caller
+#[derive(Debug, Clone, Copy)]
+pub enum ColourGrade {
+    WhiteBalance(WhiteBalance),
+    Saturation(Saturation),
+    // Degrees around the grey axis, luminance is kept
+    HueShift(f32),
+    LiftGammaGain(LiftGammaGain),
+}
+
+impl ColourGrade {
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        match self {
+            ColourGrade::WhiteBalance(balance) => balance.apply(rgb),
+            ColourGrade::Saturation(saturation) => saturation.apply(rgb),
+            ColourGrade::HueShift(degrees) => hue_rotate(rgb, *degrees),
+            ColourGrade::LiftGammaGain(lgg) => lgg.apply(rgb),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WhiteBalance {
+    // -1 cools towards blue, 1 warms towards amber
+    pub temperature: f32,
+    // -1 shifts towards green, 1 towards magenta
+    pub tint: f32,
+}
+
+impl WhiteBalance {
+    pub fn create() -> WhiteBalance {
+        WhiteBalance {
+            temperature: 0.0,
+            tint: 0.0,
+        }
+    }
+
+    pub fn temperature(mut self, temperature: f32) -> WhiteBalance {
+        assert!(
+            (-1.0..=1.0).contains(&temperature),
+            "Temperature must be between -1 and 1"
+        );
+        self.temperature = temperature;
+        self
+    }
+
+    pub fn tint(mut self, tint: f32) -> WhiteBalance {
+        assert!(
+            (-1.0..=1.0).contains(&tint),
+            "Tint must be between -1 and 1"
+        );
+        self.tint = tint;
+        self
+    }
+
+    // Channel gains in linear light, normalised so grey keeps its luminance
+    fn gains(&self) -> [f32; 3] {
+        let gains = [
+            1.0 + 0.3 * self.temperature,
+            1.0 - 0.3 * self.tint,
+            1.0 - 0.3 * self.temperature,
+        ];
+        let luminance = 0.2126 * gains[0] + 0.7152 * gains[1] + 0.0722 * gains[2];
+
+        gains.map(|gain| gain / luminance)
+    }
+
+    fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let gains = self.gains();
+
+        [0, 1, 2].map(|channel| encode_srgb(decode_srgb(rgb[channel]) * gains[channel]))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Saturation {
+    // Scales chroma around luminance, 0 is greyscale and 1 unchanged
+    pub saturation: f32,
+    // Like saturation, but weighted towards pixels that are still dull
+    pub vibrance: f32,
+}
+
+impl Saturation {
+    pub fn create() -> Saturation {
+        Saturation {
+            saturation: 1.0,
+            vibrance: 0.0,
+        }
+    }
+
+    pub fn saturation(mut self, saturation: f32) -> Saturation {
+        assert!(saturation >= 0.0, "Saturation must not be negative");
+        self.saturation = saturation;
+        self
+    }
+
+    pub fn vibrance(mut self, vibrance: f32) -> Saturation {
+        assert!(
+            (-1.0..=1.0).contains(&vibrance),
+            "Vibrance must be between -1 and 1"
+        );
+        self.vibrance = vibrance;
+        self
+    }
+
+    fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let [r, g, b] = rgb;
+        let luminance = 0.299 * r + 0.587 * g + 0.114 * b;
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let existing = if max > 0.0 { (max - min) / max } else { 0.0 };
+
+        let scale = self.saturation * (1.0 + self.vibrance * (1.0 - existing));
+
+        rgb.map(|val| luminance + (val - luminance) * scale)
+    }
+}
+
+// The hue-rotate matrix from the CSS filter effects spec
+fn hue_rotate(rgb: [f32; 3], degrees: f32) -> [f32; 3] {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let [r, g, b] = rgb;
+
+    [
+        r * (0.213 + cos * 0.787 - sin * 0.213)
+            + g * (0.715 - cos * 0.715 - sin * 0.715)
+            + b * (0.072 - cos * 0.072 + sin * 0.928),
+        r * (0.213 - cos * 0.213 + sin * 0.143)
+            + g * (0.715 + cos * 0.285 + sin * 0.140)
+            + b * (0.072 - cos * 0.072 - sin * 0.283),
+        r * (0.213 - cos * 0.213 - sin * 0.787)
+            + g * (0.715 - cos * 0.715 + sin * 0.715)
+            + b * (0.072 + cos * 0.928 + sin * 0.072),
+    ]
+}
+
+// out = (gain * (in + lift * (1 - in))) ^ (1 / gamma), per channel
+#[derive(Debug, Clone, Copy)]
+pub struct LiftGammaGain {
+    pub lift: [f32; 3],
+    pub gamma: [f32; 3],
+    pub gain: [f32; 3],
+}
+
+impl LiftGammaGain {
+    pub fn create() -> LiftGammaGain {
+        LiftGammaGain {
+            lift: [0.0; 3],
+            gamma: [1.0; 3],
+            gain: [1.0; 3],
+        }
+    }
+
+    // Raises the blacks and leaves white where it is, -1 to 1
+    pub fn lift(mut self, lift: [f32; 3]) -> LiftGammaGain {
+        assert!(
+            lift.iter().all(|val| (-1.0..=1.0).contains(val)),
+            "Lift must be between -1 and 1"
+        );
+        self.lift = lift;
+        self
+    }
+
+    // Above 1 brightens the midtones
+    pub fn gamma(mut self, gamma: [f32; 3]) -> LiftGammaGain {
+        assert!(gamma.iter().all(|val| *val > 0.0), "Gamma must be positive");
+        self.gamma = gamma;
+        self
+    }
+
+    // Scales the whole range, moving white and leaving black where it is
+    pub fn gain(mut self, gain: [f32; 3]) -> LiftGammaGain {
+        assert!(
+            gain.iter().all(|val| *val >= 0.0),
+            "Gain must not be negative"
+        );
+        self.gain = gain;
+        self
+    }
+
+    fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        [0, 1, 2].map(|channel| {
+            let val = rgb[channel];
+            let lifted = self.gain[channel] * (val + self.lift[channel] * (1.0 - val));
+
+            lifted.max(0.0).powf(1.0 / self.gamma[channel])
+        })
+    }
+}
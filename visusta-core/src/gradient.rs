@@ -0,0 +1,126 @@
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientOperator {
+    Sobel3,
+    Sobel5,
+    Scharr,
+    Prewitt,
+    Roberts,
+}
+
+impl GradientOperator {
+    // Smoothing and derivative taps of the separable x kernel, y is the transpose.
+    //  The first tap sits at the returned offset from the centre pixel
+    pub fn taps(&self) -> (&'static [f32], &'static [f32], isize) {
+        match self {
+            GradientOperator::Sobel3 => (&[1.0, 2.0, 1.0], &[-1.0, 0.0, 1.0], -1),
+            GradientOperator::Sobel5 => {
+                (&[1.0, 4.0, 6.0, 4.0, 1.0], &[-1.0, -2.0, 0.0, 2.0, 1.0], -2)
+            }
+            GradientOperator::Scharr => (&[3.0, 10.0, 3.0], &[-1.0, 0.0, 1.0], -1),
+            GradientOperator::Prewitt => (&[1.0, 1.0, 1.0], &[-1.0, 0.0, 1.0], -1),
+            // The diagonal pair rotated onto the axes, which is a 2x2 window
+            GradientOperator::Roberts => (&[1.0, 1.0], &[-1.0, 1.0], 0),
+        }
+    }
+
+    // Response to a ramp rising by 1 per pixel
+    pub fn slope_gain(&self) -> f32 {
+        let (smooth, derivative, start) = self.taps();
+        let smooth_sum: f32 = smooth.iter().sum();
+        let slope: f32 = derivative
+            .iter()
+            .enumerate()
+            .map(|(i, d)| d * (start + i as isize) as f32)
+            .sum();
+
+        smooth_sum * slope
+    }
+}
+
+// Gradients are reported in Sobel 3x3 units, where a unit step of 255
+//  gives a response of 4 * 255
+const SOBEL_STEP_GAIN: f32 = 4.0;
+const MAX_SOBEL_SQ: f32 = 255.0 * 255.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientMagnitude {
+    // Squared magnitude over 255², the measure the original thresholds were tuned on
+    Squared,
+    // Magnitude over the response to a full 0 - 255 step, linear in edge strength
+    Linear,
+}
+
+impl GradientMagnitude {
+    pub fn normal(&self, gx: f32, gy: f32) -> u8 {
+        let mag_sq = gx * gx + gy * gy;
+
+        match self {
+            GradientMagnitude::Squared => ((mag_sq / MAX_SOBEL_SQ) * 255.0).min(255.0) as u8,
+            GradientMagnitude::Linear => (mag_sq.sqrt() / SOBEL_STEP_GAIN).min(255.0) as u8,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GradientData {
+    pub operator: GradientOperator,
+    pub border: BorderMode,
+}
+
+impl GradientData {
+    pub fn create() -> GradientData {
+        GradientData {
+            operator: GradientOperator::Sobel3,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn operator(mut self, operator: GradientOperator) -> GradientData {
+        self.operator = operator;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> GradientData {
+        self.border = border;
+        self
+    }
+}
+
+// Per-pixel gradients in Sobel 3x3 units, computed once and shared by the steps after it
+#[derive(Debug, Clone)]
+pub struct GradientField {
+    pub width: usize,
+    pub height: usize,
+    pub gx: Vec<f32>,
+    pub gy: Vec<f32>,
+    // Source luminance and alpha, for steps that need the tone as well as the edges
+    pub luma: Vec<f32>,
+    pub alpha: Vec<u8>,
+    // Border policy the gradients were computed with, reused for neighbour lookups
+    pub border: BorderMode,
+}
+
+impl GradientField {
+    pub fn gx(&self, x: usize, y: usize) -> f32 {
+        self.gx[y * self.width + x]
+    }
+
+    pub fn gy(&self, x: usize, y: usize) -> f32 {
+        self.gy[y * self.width + x]
+    }
+
+    pub fn magnitude(&self, x: usize, y: usize) -> f32 {
+        self.gx(x, y).hypot(self.gy(x, y))
+    }
+
+    // Radians in -pi..=pi, y grows downwards
+    pub fn angle(&self, x: usize, y: usize) -> f32 {
+        self.gy(x, y).atan2(self.gx(x, y))
+    }
+
+    pub fn normal(&self, x: usize, y: usize, measure: GradientMagnitude) -> u8 {
+        measure.normal(self.gx(x, y), self.gy(x, y))
+    }
+}
@@ -0,0 +1,114 @@
+use crate::colour::{decode_srgb, encode_srgb, linear_to_oklab, oklab_to_linear};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientSpace {
+    // Straight between the stored values, as most editors do
+    Srgb,
+    // Physically even light, mid tones come out brighter than in sRGB
+    Linear,
+    // Perceptually even steps, avoids the grey dip between complementary colours
+    Oklab,
+}
+
+impl GradientSpace {
+    fn coords(self, rgb: [u8; 3]) -> [f32; 3] {
+        let encoded = rgb.map(|val| val as f32 / 255.0);
+        match self {
+            GradientSpace::Srgb => encoded,
+            GradientSpace::Linear => encoded.map(decode_srgb),
+            GradientSpace::Oklab => linear_to_oklab(encoded.map(decode_srgb)),
+        }
+    }
+
+    fn colour(self, val: [f32; 3]) -> [u8; 3] {
+        let encoded = match self {
+            GradientSpace::Srgb => val,
+            GradientSpace::Linear => val.map(|channel| encode_srgb(channel.clamp(0.0, 1.0))),
+            GradientSpace::Oklab => {
+                oklab_to_linear(val).map(|channel| encode_srgb(channel.clamp(0.0, 1.0)))
+            }
+        };
+
+        encoded.map(|channel| (channel * 255.0).round().clamp(0.0, 255.0) as u8)
+    }
+}
+
+// Luminance 0 - 255 mapped along colour stops, positions are 0 - 1
+#[derive(Debug, Clone)]
+pub struct GradientMap {
+    pub stops: Vec<(f32, [u8; 4])>,
+    pub space: GradientSpace,
+}
+
+impl GradientMap {
+    pub fn create(stops: &[(f32, [u8; 4])]) -> GradientMap {
+        assert!(stops.len() >= 2, "Gradient map needs at least 2 stops");
+        assert!(
+            stops.iter().all(|(pos, _)| (0.0..=1.0).contains(pos)),
+            "Stop positions must be between 0 and 1"
+        );
+
+        let mut stops = stops.to_vec();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        GradientMap {
+            stops,
+            space: GradientSpace::Srgb,
+        }
+    }
+
+    // Shadows to highlights through two inks, blended in OKLab so the mid tones stay clean
+    pub fn duotone(shadow: [u8; 3], highlight: [u8; 3]) -> GradientMap {
+        let [sr, sg, sb] = shadow;
+        let [hr, hg, hb] = highlight;
+
+        GradientMap::create(&[(0.0, [sr, sg, sb, 255]), (1.0, [hr, hg, hb, 255])])
+            .space(GradientSpace::Oklab)
+    }
+
+    pub fn sepia() -> GradientMap {
+        GradientMap::create(&[
+            (0.0, [28, 17, 8, 255]),
+            (0.5, [150, 106, 62, 255]),
+            (1.0, [255, 243, 222, 255]),
+        ])
+    }
+
+    pub fn space(mut self, space: GradientSpace) -> GradientMap {
+        self.space = space;
+        self
+    }
+
+    // Position 0 - 1, clamped to the end stops
+    pub fn sample(&self, position: f32) -> [u8; 4] {
+        let last = self.stops.len() - 1;
+
+        if position <= self.stops[0].0 {
+            return self.stops[0].1;
+        }
+        if position >= self.stops[last].0 {
+            return self.stops[last].1;
+        }
+
+        let idx = self.stops.partition_point(|(pos, _)| *pos <= position) - 1;
+        let (start, from) = self.stops[idx];
+        let (end, to) = self.stops[idx + 1];
+
+        let t = (position - start) / (end - start);
+
+        let low = self.space.coords([from[0], from[1], from[2]]);
+        let high = self.space.coords([to[0], to[1], to[2]]);
+        let [r, g, b] = self
+            .space
+            .colour([0, 1, 2].map(|channel| low[channel] + (high[channel] - low[channel]) * t));
+
+        let alpha = from[3] as f32 + (to[3] as f32 - from[3] as f32) * t;
+
+        [r, g, b, alpha.round() as u8]
+    }
+
+    // One colour per luminance value
+    pub fn build_lut(&self) -> [[u8; 4]; 256] {
+        std::array::from_fn(|idx| self.sample(idx as f32 / 255.0))
+    }
+}
@@ -0,0 +1,106 @@
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DotShape {
+    Round,
+    Line,
+    Diamond,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HalftoneData {
+    // Screen cell size in pixels
+    pub cell_size: f32,
+    // Screen angle in degrees
+    pub angle: f32,
+    pub shape: DotShape,
+    pub anti_alias: bool,
+    pub border: BorderMode,
+}
+
+impl HalftoneData {
+    pub fn create() -> HalftoneData {
+        HalftoneData {
+            cell_size: 8.0,
+            angle: 45.0,
+            shape: DotShape::Round,
+            anti_alias: true,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn cell_size(mut self, cell_size: f32) -> HalftoneData {
+        assert!(cell_size >= 1.0, "Cell size must be at least one pixel");
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn angle(mut self, angle: f32) -> HalftoneData {
+        self.angle = angle;
+        self
+    }
+
+    pub fn shape(mut self, shape: DotShape) -> HalftoneData {
+        self.shape = shape;
+        self
+    }
+
+    pub fn anti_alias(mut self, anti_alias: bool) -> HalftoneData {
+        self.anti_alias = anti_alias;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> HalftoneData {
+        self.border = border;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CmykHalftoneData {
+    pub cell_size: f32,
+    // Screen angles in degrees for cyan, magenta, yellow and black
+    pub angles: [f32; 4],
+    pub shape: DotShape,
+    pub anti_alias: bool,
+    pub border: BorderMode,
+}
+
+impl CmykHalftoneData {
+    // Classic print angles, yellow sits at 0 where its moire is least visible
+    pub fn create() -> CmykHalftoneData {
+        CmykHalftoneData {
+            cell_size: 8.0,
+            angles: [15.0, 75.0, 0.0, 45.0],
+            shape: DotShape::Round,
+            anti_alias: true,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn cell_size(mut self, cell_size: f32) -> CmykHalftoneData {
+        assert!(cell_size >= 1.0, "Cell size must be at least one pixel");
+        self.cell_size = cell_size;
+        self
+    }
+
+    pub fn angles(mut self, angles: [f32; 4]) -> CmykHalftoneData {
+        self.angles = angles;
+        self
+    }
+
+    pub fn shape(mut self, shape: DotShape) -> CmykHalftoneData {
+        self.shape = shape;
+        self
+    }
+
+    pub fn anti_alias(mut self, anti_alias: bool) -> CmykHalftoneData {
+        self.anti_alias = anti_alias;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> CmykHalftoneData {
+        self.border = border;
+        self
+    }
+}
@@ -0,0 +1,84 @@
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HatchOrientation {
+    // Strokes run along the gradient, across the edges
+    Gradient,
+    // Strokes run along the edges, following the contours
+    Perpendicular,
+}
+
+#[derive(Debug, Clone)]
+pub struct HatchData {
+    // Luminance bands, the lightest band is left as paper
+    pub levels: u8,
+    // Up to 4 hatch layers, each darker band adds one more
+    pub layers: u8,
+    pub spacing: f32,
+    pub thickness: f32,
+    pub orientation: HatchOrientation,
+    // Below this normalised Sobel magnitude the default angle is used
+    pub magnitude_min: u8,
+    // Degrees, used for flat regions with no usable gradient
+    pub default_angle: f32,
+    pub border: BorderMode,
+}
+
+impl HatchData {
+    pub fn create() -> HatchData {
+        HatchData {
+            levels: 5,
+            layers: 4,
+            spacing: 6.0,
+            thickness: 1.0,
+            orientation: HatchOrientation::Perpendicular,
+            magnitude_min: 10,
+            default_angle: 45.0,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn levels(mut self, levels: u8) -> HatchData {
+        assert!(levels >= 2, "Hatching needs at least two luminance bands");
+        self.levels = levels;
+        self
+    }
+
+    pub fn layers(mut self, layers: u8) -> HatchData {
+        assert!((1..=4).contains(&layers), "Hatching supports 1 to 4 layers");
+        self.layers = layers;
+        self
+    }
+
+    pub fn spacing(mut self, spacing: f32) -> HatchData {
+        assert!(spacing >= 2.0, "Stroke spacing must be at least 2 pixels");
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn thickness(mut self, thickness: f32) -> HatchData {
+        assert!(thickness > 0.0, "Stroke thickness must be positive");
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn orientation(mut self, orientation: HatchOrientation) -> HatchData {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn magnitude_min(mut self, magnitude_min: u8) -> HatchData {
+        self.magnitude_min = magnitude_min;
+        self
+    }
+
+    pub fn default_angle(mut self, default_angle: f32) -> HatchData {
+        self.default_angle = default_angle;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> HatchData {
+        self.border = border;
+        self
+    }
+}
@@ -0,0 +1,64 @@
+#[derive(Debug, Clone, Copy)]
+pub enum HistogramFilter {
+    Equalize,
+    Clahe(ClaheData),
+    AutoContrast(AutoContrast),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClaheData {
+    pub tiles_x: u32,
+    pub tiles_y: u32,
+    pub clip_limit: f32,
+}
+
+impl ClaheData {
+    pub fn create() -> ClaheData {
+        ClaheData {
+            tiles_x: 8,
+            tiles_y: 8,
+            clip_limit: 2.0,
+        }
+    }
+
+    pub fn tiles(mut self, tiles_x: u32, tiles_y: u32) -> ClaheData {
+        assert!(tiles_x > 0 && tiles_y > 0, "Tile grid must not be empty");
+        self.tiles_x = tiles_x;
+        self.tiles_y = tiles_y;
+        self
+    }
+
+    // Multiple of the mean bin height a tile histogram bin can reach
+    //  before the excess is redistributed
+    pub fn clip_limit(mut self, clip_limit: f32) -> ClaheData {
+        assert!(clip_limit >= 1.0, "Clip limit must be at least 1");
+        self.clip_limit = clip_limit;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AutoContrast {
+    pub low: f32,
+    pub high: f32,
+}
+
+impl AutoContrast {
+    pub fn create() -> AutoContrast {
+        AutoContrast {
+            low: 0.01,
+            high: 0.99,
+        }
+    }
+
+    // Percentiles as fractions of the visible pixels, values outside are clipped
+    pub fn percentiles(mut self, low: f32, high: f32) -> AutoContrast {
+        assert!(
+            (0.0..1.0).contains(&low) && low < high && high <= 1.0,
+            "Percentiles must satisfy 0 <= low < high <= 1"
+        );
+        self.low = low;
+        self.high = high;
+        self
+    }
+}
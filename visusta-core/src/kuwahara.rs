@@ -0,0 +1,82 @@
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy)]
+pub struct KuwaharaData {
+    pub radius: u32,
+    // Exponent on the sector deviation, higher picks the flattest sector harder
+    pub sharpness: f32,
+    pub mode: KuwaharaMode,
+    pub border: BorderMode,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum KuwaharaMode {
+    // 4 square quadrants, the lowest variance quadrant's mean wins
+    Classic,
+    // 8 weighted sectors of a disc, blended by their deviation
+    Generalized,
+    // 8 sectors of an ellipse aligned to the local structure tensor
+    Anisotropic(AnisotropicData),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AnisotropicData {
+    pub tensor_sigma: f32,
+    // Lower values stretch the ellipse further along strong edges
+    pub alpha: f32,
+}
+
+impl KuwaharaData {
+    pub fn create(radius: u32) -> KuwaharaData {
+        assert!(radius > 0, "Radius must be positive");
+
+        KuwaharaData {
+            radius,
+            sharpness: 8.0,
+            mode: KuwaharaMode::Classic,
+            border: BorderMode::Reflect,
+        }
+    }
+
+    pub fn sharpness(mut self, sharpness: f32) -> KuwaharaData {
+        assert!(sharpness > 0.0, "Sharpness must be positive");
+        self.sharpness = sharpness;
+        self
+    }
+
+    pub fn generalized(mut self) -> KuwaharaData {
+        self.mode = KuwaharaMode::Generalized;
+        self
+    }
+
+    pub fn anisotropic(mut self, data: AnisotropicData) -> KuwaharaData {
+        self.mode = KuwaharaMode::Anisotropic(data);
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> KuwaharaData {
+        self.border = border;
+        self
+    }
+}
+
+impl AnisotropicData {
+    pub fn create() -> AnisotropicData {
+        AnisotropicData {
+            tensor_sigma: 2.0,
+            alpha: 1.0,
+        }
+    }
+
+    pub fn tensor_sigma(mut self, tensor_sigma: f32) -> AnisotropicData {
+        assert!(tensor_sigma > 0.0, "Tensor sigma must be positive");
+        self.tensor_sigma = tensor_sigma;
+        self
+    }
+
+    pub fn alpha(mut self, alpha: f32) -> AnisotropicData {
+        assert!(alpha > 0.0, "Alpha must be positive");
+        self.alpha = alpha;
+        self
+    }
+}
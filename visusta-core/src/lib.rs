@@ -1,11 +1,59 @@
 use async_trait::async_trait;
-use image::{ImageBuffer, LumaA, RgbaImage};
+use image::{ImageBuffer, LumaA, Rgba32FImage, RgbaImage};
 
-use crate::gaussians::{GaussianBuilder, GaussianColorData};
+use crate::auxiliary::{BlendData, SizePolicy};
+use crate::border::BorderMode;
+use crate::canny::{CannyData, CannyThresholds};
+use crate::channel::{ChannelMapping, DirectionHue};
+use crate::effects::{BloomData, GrainData, VignetteData};
+use crate::float::FloatRange;
+use crate::gaussians::{DogOutput, GaussianBuilder};
+use crate::grade::ColourGrade;
+use crate::gradient::{GradientData, GradientField, GradientMagnitude, GradientOperator};
+use crate::gradient_map::GradientMap;
+use crate::halftone::{CmykHalftoneData, HalftoneData};
+use crate::hatch::HatchData;
+use crate::histogram::HistogramFilter;
+use crate::kuwahara::KuwaharaData;
+use crate::lowpoly::{LowPolyData, TriangleMesh};
+use crate::lut::LutData;
+use crate::mask::{LuminanceMask, MaskData};
+use crate::morphology::MorphologyOp;
+use crate::mosaic::{MosaicData, TileGrid};
 use crate::pipeline::LayerOutput;
+use crate::pixelsort::PixelSortData;
+use crate::threshold::{Cutoff, PosterizeData, ThresholdFilter};
+use crate::tone::ToneFilter;
+use crate::voronoi::{StainedGlassData, StippleData, StippleSet};
+use crate::xdog::XDogData;
 
+pub mod auxiliary;
+pub mod border;
+pub mod canny;
+pub mod channel;
+pub mod colour;
+pub mod effects;
+pub mod float;
 pub mod gaussians;
+pub mod grade;
+pub mod gradient;
+pub mod gradient_map;
+pub mod halftone;
+pub mod hatch;
+pub mod histogram;
+pub mod kuwahara;
+pub mod lowpoly;
+pub mod lut;
+pub mod mask;
+pub mod morphology;
+pub mod mosaic;
 pub mod pipeline;
+pub mod pixelsort;
+pub mod random;
+pub mod threshold;
+pub mod tone;
+pub mod voronoi;
+pub mod xdog;
 
 #[derive(Debug, Clone, Copy)]
 pub struct LuminanceFilter {
@@ -34,20 +82,17 @@ impl LuminanceFilter {
 
 #[derive(Debug, Clone)]
 pub struct SobelColorData {
-    pub magnitude_min: u8,
-    pub r: SobelColorItem,
-    pub g: SobelColorItem,
-    pub b: SobelColorItem,
-    pub a: SobelColorItem,
+    pub operator: GradientOperator,
+    pub magnitude: GradientMagnitude,
+    pub magnitude_min: Cutoff,
+    pub border: BorderMode,
+    pub colouring: SobelColouring,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum SobelColorItem {
-    NormalScale(f32),
-    GxScale(f32),
-    GyScale(f32),
-    Absolute(u8),
-    None,
+#[derive(Debug, Clone)]
+pub enum SobelColouring {
+    Channels(ChannelMapping),
+    DirectionHue(DirectionHue),
 }
 
 #[derive(Debug, Clone)]
@@ -67,7 +112,10 @@ pub enum AsciiSpaceType {
 #[derive(Debug, Clone)]
 pub struct SobelAscii {
     pub font_size: usize,
-    pub magnitude_min: u8,
+    pub operator: GradientOperator,
+    pub magnitude: GradientMagnitude,
+    pub magnitude_min: Cutoff,
+    pub border: BorderMode,
     pub ascii_max: f32,
     pub chars: [char; 4],
     pub space_type: AsciiSpaceType,
@@ -102,7 +150,10 @@ impl SobelAscii {
     pub fn create() -> Self {
         SobelAscii {
             font_size: 10,
-            magnitude_min: 10,
+            operator: GradientOperator::Sobel3,
+            magnitude: GradientMagnitude::Squared,
+            magnitude_min: Cutoff::Value(10.0),
+            border: BorderMode::Clamp,
             ascii_max: 0.65,
             chars: ['|', '/', '─', '\\'],
             space_type: AsciiSpaceType::Space,
@@ -114,8 +165,28 @@ impl SobelAscii {
         self
     }
 
+    pub fn operator(mut self, operator: GradientOperator) -> Self {
+        self.operator = operator;
+        self
+    }
+
+    pub fn magnitude(mut self, magnitude: GradientMagnitude) -> Self {
+        self.magnitude = magnitude;
+        self
+    }
+
     pub fn magnitude_min(mut self, magnitude_min: u8) -> Self {
-        self.magnitude_min = magnitude_min;
+        self.magnitude_min = Cutoff::Value(magnitude_min as f32);
+        self
+    }
+
+    pub fn magnitude_min_otsu(mut self) -> Self {
+        self.magnitude_min = Cutoff::Otsu;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> Self {
+        self.border = border;
         self
     }
 
@@ -135,8 +206,119 @@ impl SobelAscii {
     }
 }
 
+// Colouring for a GradientField, the operator and border were fixed when the field was computed
+#[derive(Debug, Clone)]
+pub struct GradientColourData {
+    pub magnitude: GradientMagnitude,
+    pub magnitude_min: Cutoff,
+    pub colouring: SobelColouring,
+}
+
+impl GradientColourData {
+    pub fn create(colouring: SobelColouring) -> Self {
+        GradientColourData {
+            magnitude: GradientMagnitude::Squared,
+            magnitude_min: Cutoff::Value(10.0),
+            colouring,
+        }
+    }
+
+    pub fn magnitude(mut self, magnitude: GradientMagnitude) -> Self {
+        self.magnitude = magnitude;
+        self
+    }
+
+    pub fn magnitude_min(mut self, magnitude_min: u8) -> Self {
+        self.magnitude_min = Cutoff::Value(magnitude_min as f32);
+        self
+    }
+
+    pub fn magnitude_min_otsu(mut self) -> Self {
+        self.magnitude_min = Cutoff::Otsu;
+        self
+    }
+}
+
+impl From<&SobelColorData> for GradientColourData {
+    fn from(data: &SobelColorData) -> Self {
+        GradientColourData {
+            magnitude: data.magnitude,
+            magnitude_min: data.magnitude_min,
+            colouring: data.colouring.clone(),
+        }
+    }
+}
+
+// Ascii for a GradientField, the operator and border were fixed when the field was computed
+#[derive(Debug, Clone)]
+pub struct GradientAsciiData {
+    pub font_size: usize,
+    pub magnitude: GradientMagnitude,
+    pub magnitude_min: Cutoff,
+    pub ascii_max: f32,
+    pub chars: [char; 4],
+    pub space_type: AsciiSpaceType,
+}
+
+impl GradientAsciiData {
+    pub fn create() -> Self {
+        GradientAsciiData::from(&SobelAscii::create())
+    }
+
+    pub fn font_size(mut self, font_size: usize) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn magnitude(mut self, magnitude: GradientMagnitude) -> Self {
+        self.magnitude = magnitude;
+        self
+    }
+
+    pub fn magnitude_min(mut self, magnitude_min: u8) -> Self {
+        self.magnitude_min = Cutoff::Value(magnitude_min as f32);
+        self
+    }
+
+    pub fn magnitude_min_otsu(mut self) -> Self {
+        self.magnitude_min = Cutoff::Otsu;
+        self
+    }
+
+    pub fn ascii_max(mut self, ascii_max: f32) -> Self {
+        self.ascii_max = ascii_max;
+        self
+    }
+
+    pub fn chars(mut self, chars: [char; 4]) -> Self {
+        self.chars = chars;
+        self
+    }
+
+    pub fn space_type(mut self, space_type: AsciiSpaceType) -> Self {
+        self.space_type = space_type;
+        self
+    }
+}
+
+impl From<&SobelAscii> for GradientAsciiData {
+    fn from(data: &SobelAscii) -> Self {
+        GradientAsciiData {
+            font_size: data.font_size,
+            magnitude: data.magnitude,
+            magnitude_min: data.magnitude_min,
+            ascii_max: data.ascii_max,
+            chars: data.chars,
+            space_type: data.space_type,
+        }
+    }
+}
+
 pub type LumaAImage = ImageBuffer<LumaA<u8>, Vec<u8>>;
 
+// Float luminance, 0.0 - 1.0 is the u8 range but values may go past it
+pub type LumaA32FImage = ImageBuffer<LumaA<f32>, Vec<f32>>;
+
 pub struct CharImage {
     pub width: usize,
     pub height: usize,
@@ -149,7 +331,13 @@ pub trait VisustaProcessor {
 
     async fn luma_to_rgba(&self, img: &LumaAImage) -> RgbaImage;
 
-    async fn sobel_to_colour(&self, img: &LumaAImage, filter: SobelColorData) -> RgbaImage;
+    // `original` backs ChannelSource::Original, grey from luminance when None
+    async fn sobel_to_colour(
+        &self,
+        img: &LumaAImage,
+        filter: SobelColorData,
+        original: Option<&RgbaImage>,
+    ) -> RgbaImage;
 
     async fn sobel_ascii_directional(&self, img: &LumaAImage, filter: SobelAscii) -> CharImage;
 
@@ -159,11 +347,164 @@ pub trait VisustaProcessor {
         &self,
         img: &LumaAImage,
         builder: GaussianBuilder,
-        filter: GaussianColorData,
+        channels: ChannelMapping,
+        original: Option<&RgbaImage>,
     ) -> RgbaImage;
 
     async fn luminance_to_ascii(&self, img: &LumaAImage, filter: LuminanceAsciiFilter)
     -> CharImage;
 
+    async fn tone_rgba(&self, img: &RgbaImage, filter: ToneFilter) -> RgbaImage;
+
+    async fn tone_luma(&self, img: &LumaAImage, filter: ToneFilter) -> LumaAImage;
+
+    async fn histogram_luma(&self, img: &LumaAImage, filter: HistogramFilter) -> LumaAImage;
+
+    async fn canny_on_luma(&self, img: &LumaAImage, data: CannyData) -> LumaAImage;
+
+    async fn blur_luma(&self, img: &LumaAImage, sigma: f32, border: BorderMode) -> LumaAImage;
+
+    async fn blur_rgba(&self, img: &RgbaImage, sigma: f32, border: BorderMode) -> RgbaImage;
+
+    async fn dog_on_luma(
+        &self,
+        img: &LumaAImage,
+        builder: GaussianBuilder,
+        output: DogOutput,
+    ) -> LumaAImage;
+
+    async fn xdog_on_luma(&self, img: &LumaAImage, data: XDogData) -> LumaAImage;
+
+    async fn kuwahara_rgba(&self, img: &RgbaImage, data: KuwaharaData) -> RgbaImage;
+
+    async fn grain_rgba(&self, img: &RgbaImage, data: GrainData) -> RgbaImage;
+
+    async fn vignette_rgba(&self, img: &RgbaImage, data: VignetteData) -> RgbaImage;
+
+    async fn bloom_rgba(&self, img: &RgbaImage, data: BloomData) -> RgbaImage;
+
+    async fn mosaic_rgba(&self, img: &RgbaImage, data: MosaicData) -> RgbaImage;
+
+    async fn mosaic_rgba_grid(&self, img: &RgbaImage, data: MosaicData) -> (RgbaImage, TileGrid);
+
+    async fn low_poly_rgba(&self, img: &RgbaImage, data: LowPolyData) -> RgbaImage;
+
+    async fn low_poly_mesh(&self, img: &RgbaImage, data: LowPolyData) -> TriangleMesh;
+
+    async fn stained_glass_rgba(&self, img: &RgbaImage, data: StainedGlassData) -> RgbaImage;
+
+    async fn stipple_rgba(&self, img: &RgbaImage, data: StippleData) -> RgbaImage;
+
+    async fn stipple_points(&self, img: &RgbaImage, data: StippleData) -> StippleSet;
+
+    async fn halftone_rgba(&self, img: &RgbaImage, data: HalftoneData) -> LumaAImage;
+
+    async fn halftone_cmyk(&self, img: &RgbaImage, data: CmykHalftoneData) -> RgbaImage;
+
+    async fn pixel_sort_rgba(&self, img: &RgbaImage, data: PixelSortData) -> RgbaImage;
+
+    async fn hatch_rgba(
+        &self,
+        img: &RgbaImage,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage;
+
+    async fn hatch_luma(
+        &self,
+        img: &LumaAImage,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage;
+
+    async fn threshold_luma(&self, img: &LumaAImage, filter: ThresholdFilter) -> LumaAImage;
+
+    async fn posterize_rgba(&self, img: &RgbaImage, data: PosterizeData) -> RgbaImage;
+
+    async fn morphology_luma(&self, img: &LumaAImage, op: MorphologyOp) -> LumaAImage;
+
+    async fn gradient_luma(&self, img: &LumaAImage, data: GradientData) -> GradientField;
+
+    async fn gradient_to_colour(
+        &self,
+        field: &GradientField,
+        filter: GradientColourData,
+        original: Option<&RgbaImage>,
+    ) -> RgbaImage;
+
+    async fn gradient_to_luma(
+        &self,
+        field: &GradientField,
+        measure: GradientMagnitude,
+    ) -> LumaAImage;
+
+    async fn gradient_hatch(
+        &self,
+        field: &GradientField,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage;
+
+    async fn gradient_edges(
+        &self,
+        field: &GradientField,
+        thresholds: CannyThresholds,
+    ) -> LumaAImage;
+
+    async fn gradient_to_ascii(
+        &self,
+        field: &GradientField,
+        filter: GradientAsciiData,
+    ) -> CharImage;
+
+    async fn rgba_to_float(&self, img: &RgbaImage) -> Rgba32FImage;
+
+    async fn rgba_from_float(&self, img: &Rgba32FImage, range: FloatRange) -> RgbaImage;
+
+    async fn luma_to_float(&self, img: &LumaAImage) -> LumaA32FImage;
+
+    async fn luma_from_float(&self, img: &LumaA32FImage, range: FloatRange) -> LumaAImage;
+
+    async fn rgba_to_luma_f32(&self, img: &Rgba32FImage, filter: LuminanceFilter) -> LumaA32FImage;
+
+    async fn blur_rgba_f32(
+        &self,
+        img: &Rgba32FImage,
+        sigma: f32,
+        border: BorderMode,
+    ) -> Rgba32FImage;
+
+    async fn blur_luma_f32(
+        &self,
+        img: &LumaA32FImage,
+        sigma: f32,
+        border: BorderMode,
+    ) -> LumaA32FImage;
+
+    async fn dog_on_luma_f32(&self, img: &LumaA32FImage, builder: GaussianBuilder)
+    -> LumaA32FImage;
+
+    async fn gradient_luma_f32(&self, img: &LumaA32FImage, data: GradientData) -> GradientField;
+
+    async fn luminance_mask(&self, img: &LumaAImage, data: LuminanceMask) -> LumaAImage;
+
+    async fn apply_mask(&self, img: &RgbaImage, mask: &LumaAImage, data: MaskData) -> RgbaImage;
+
+    async fn fit_rgba(
+        &self,
+        img: &RgbaImage,
+        width: u32,
+        height: u32,
+        fit: SizePolicy,
+    ) -> RgbaImage;
+
+    async fn blend_rgba(&self, img: &RgbaImage, top: &RgbaImage, data: BlendData) -> RgbaImage;
+
+    async fn lut_rgba(&self, img: &RgbaImage, data: LutData) -> RgbaImage;
+
+    async fn grade_rgba(&self, img: &RgbaImage, grade: ColourGrade) -> RgbaImage;
+
+    async fn gradient_map(&self, img: &LumaAImage, map: GradientMap) -> RgbaImage;
+
     async fn overlay_layers(&self, layers: &[LayerOutput]) -> Option<LayerOutput>;
 }
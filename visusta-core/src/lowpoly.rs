@@ -0,0 +1,94 @@
+use std::fmt::Write;
+
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy)]
+pub enum TriangleFill {
+    // Average of every pixel the triangle covers
+    Mean,
+    // Single sample at the triangle's centroid
+    Centroid,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LowPolyData {
+    pub point_count: u32,
+    pub seed: u64,
+    // 0 samples points uniformly, 1 samples purely by Sobel magnitude
+    pub edge_bias: f32,
+    pub fill: TriangleFill,
+    // Only used for the Sobel pass that weights the point sampling
+    pub border: BorderMode,
+}
+
+impl LowPolyData {
+    pub fn create(seed: u64) -> LowPolyData {
+        LowPolyData {
+            point_count: 1000,
+            seed,
+            edge_bias: 0.8,
+            fill: TriangleFill::Mean,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn point_count(mut self, point_count: u32) -> LowPolyData {
+        assert!(point_count > 0, "Point count must be positive");
+        self.point_count = point_count;
+        self
+    }
+
+    pub fn edge_bias(mut self, edge_bias: f32) -> LowPolyData {
+        self.edge_bias = edge_bias.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn fill(mut self, fill: TriangleFill) -> LowPolyData {
+        self.fill = fill;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> LowPolyData {
+        self.border = border;
+        self
+    }
+}
+
+pub struct TriangleMesh {
+    pub width: u32,
+    pub height: u32,
+    pub points: Vec<(f32, f32)>,
+    pub triangles: Vec<[usize; 3]>,
+    pub colours: Vec<[u8; 4]>,
+}
+
+impl TriangleMesh {
+    pub fn to_svg(&self) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = self.width,
+            h = self.height
+        );
+
+        for (triangle, colour) in self.triangles.iter().zip(self.colours.iter()) {
+            let [a, b, c] = triangle.map(|idx| self.points[idx]);
+            let fill = format!("#{:02x}{:02x}{:02x}", colour[0], colour[1], colour[2]);
+
+            // The matching stroke hides the hairline seams renderers leave between polygons
+            let _ = write!(
+                svg,
+                "  <polygon points=\"{:.2},{:.2} {:.2},{:.2} {:.2},{:.2}\" fill=\"{fill}\" stroke=\"{fill}\" stroke-width=\"0.5\"",
+                a.0, a.1, b.0, b.1, c.0, c.1
+            );
+
+            if colour[3] < 255 {
+                let _ = write!(svg, " fill-opacity=\"{:.3}\"", colour[3] as f32 / 255.0);
+            }
+
+            svg.push_str("/>\n");
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
@@ -0,0 +1,300 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum CubeError {
+    Io(std::io::Error),
+    // 1-based line number in the file
+    Parse { line: usize, message: String },
+    MissingSize,
+    // A domain minimum at or above its maximum
+    Domain,
+    EntryCount { expected: usize, got: usize },
+}
+
+impl fmt::Display for CubeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CubeError::Io(err) => write!(f, "could not read cube file: {err}"),
+            CubeError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            CubeError::MissingSize => write!(f, "no LUT_3D_SIZE before the table"),
+            CubeError::Domain => write!(f, "domain min must be below domain max"),
+            CubeError::EntryCount { expected, got } => {
+                write!(f, "expected {expected} table entries, got {got}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CubeError {}
+
+impl From<std::io::Error> for CubeError {
+    fn from(err: std::io::Error) -> Self {
+        CubeError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LutInterpolation {
+    Trilinear,
+    // Splits each cell into 6 tetrahedra, keeps the grey axis exact and costs 4 reads, not 8
+    Tetrahedral,
+}
+
+// A 3D table in the Adobe / Resolve .cube layout, red changing fastest
+#[derive(Debug, Clone)]
+pub struct CubeLut {
+    pub title: Option<String>,
+    pub size: usize,
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    pub table: Arc<[[f32; 3]]>,
+}
+
+impl CubeLut {
+    pub fn identity(size: usize) -> CubeLut {
+        assert!(size >= 2, "Cube needs at least 2 points per axis");
+
+        let step = 1.0 / (size - 1) as f32;
+        let table = (0..size * size * size)
+            .map(|idx| {
+                [
+                    (idx % size) as f32 * step,
+                    (idx / size % size) as f32 * step,
+                    (idx / (size * size)) as f32 * step,
+                ]
+            })
+            .collect();
+
+        CubeLut {
+            title: None,
+            size,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            table,
+        }
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> Result<CubeLut, CubeError> {
+        CubeLut::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(text: &str) -> Result<CubeLut, CubeError> {
+        let mut title = None;
+        let mut size = None;
+        let mut domain_min = [0.0; 3];
+        let mut domain_max = [1.0; 3];
+        let mut table = Vec::new();
+
+        for (idx, line) in text.lines().enumerate() {
+            let line_no = idx + 1;
+            let parse_err = |message: &str| CubeError::Parse {
+                line: line_no,
+                message: message.to_string(),
+            };
+
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or("");
+
+            if keyword.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                if !table.is_empty() {
+                    return Err(parse_err("keyword after the table started"));
+                }
+
+                match keyword {
+                    "TITLE" => {
+                        let rest = line["TITLE".len()..].trim();
+                        title = Some(rest.trim_matches('"').to_string());
+                    }
+                    "LUT_3D_SIZE" => {
+                        let val: usize = words
+                            .next()
+                            .and_then(|word| word.parse().ok())
+                            .ok_or_else(|| parse_err("LUT_3D_SIZE needs a whole number"))?;
+                        if !(2..=256).contains(&val) {
+                            return Err(parse_err("LUT_3D_SIZE must be between 2 and 256"));
+                        }
+                        size = Some(val);
+                    }
+                    "DOMAIN_MIN" => {
+                        domain_min =
+                            triple(words).ok_or_else(|| parse_err("DOMAIN_MIN needs 3 numbers"))?
+                    }
+                    "DOMAIN_MAX" => {
+                        domain_max =
+                            triple(words).ok_or_else(|| parse_err("DOMAIN_MAX needs 3 numbers"))?
+                    }
+                    // Resolve writes a single range shared by all channels
+                    "LUT_3D_INPUT_RANGE" => {
+                        let (Some(min), Some(max), None) = (
+                            words.next().and_then(|word| word.parse().ok()),
+                            words.next().and_then(|word| word.parse().ok()),
+                            words.next(),
+                        ) else {
+                            return Err(parse_err("LUT_3D_INPUT_RANGE needs 2 numbers"));
+                        };
+                        domain_min = [min; 3];
+                        domain_max = [max; 3];
+                    }
+                    "LUT_1D_SIZE" | "LUT_1D_INPUT_RANGE" => {
+                        return Err(parse_err("1D cube files are not supported"));
+                    }
+                    _ => return Err(parse_err("unknown keyword")),
+                }
+                continue;
+            }
+
+            if size.is_none() {
+                return Err(CubeError::MissingSize);
+            }
+
+            let entry = triple(line.split_whitespace())
+                .ok_or_else(|| parse_err("table rows need 3 numbers"))?;
+            table.push(entry);
+        }
+
+        let size = size.ok_or(CubeError::MissingSize)?;
+
+        if (0..3).any(|channel| domain_min[channel] >= domain_max[channel]) {
+            return Err(CubeError::Domain);
+        }
+
+        let expected = size * size * size;
+        if table.len() != expected {
+            return Err(CubeError::EntryCount {
+                expected,
+                got: table.len(),
+            });
+        }
+
+        Ok(CubeLut {
+            title,
+            size,
+            domain_min,
+            domain_max,
+            table: table.into(),
+        })
+    }
+
+    fn entry(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.table[r + g * self.size + b * self.size * self.size]
+    }
+
+    // Input in 0 - 1 encoded RGB, the output is whatever the table holds
+    pub fn lookup(&self, rgb: [f32; 3], interpolation: LutInterpolation) -> [f32; 3] {
+        let last = (self.size - 1) as f32;
+
+        let mut base = [0usize; 3];
+        let mut frac = [0f32; 3];
+
+        for channel in 0..3 {
+            let normal = (rgb[channel] - self.domain_min[channel])
+                / (self.domain_max[channel] - self.domain_min[channel]);
+            let pos = (normal * last).clamp(0.0, last);
+
+            // The last cell is reached from below, so the upper corner always exists
+            let cell = (pos.floor() as usize).min(self.size - 2);
+            base[channel] = cell;
+            frac[channel] = pos - cell as f32;
+        }
+
+        let [r, g, b] = base;
+        let [fr, fg, fb] = frac;
+        let corner = |dr: usize, dg: usize, db: usize| self.entry(r + dr, g + dg, b + db);
+
+        match interpolation {
+            LutInterpolation::Trilinear => {
+                let lerp = |a: [f32; 3], b: [f32; 3], t: f32| {
+                    [0, 1, 2].map(|channel| a[channel] + (b[channel] - a[channel]) * t)
+                };
+
+                let c00 = lerp(corner(0, 0, 0), corner(1, 0, 0), fr);
+                let c10 = lerp(corner(0, 1, 0), corner(1, 1, 0), fr);
+                let c01 = lerp(corner(0, 0, 1), corner(1, 0, 1), fr);
+                let c11 = lerp(corner(0, 1, 1), corner(1, 1, 1), fr);
+
+                lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+            }
+            LutInterpolation::Tetrahedral => {
+                // Walk from the black corner to the white one along the axes in order of size
+                let (first, second) = if fr >= fg && fg >= fb {
+                    ((1, 0, 0), (1, 1, 0))
+                } else if fr >= fb && fb >= fg {
+                    ((1, 0, 0), (1, 0, 1))
+                } else if fb >= fr && fr >= fg {
+                    ((0, 0, 1), (1, 0, 1))
+                } else if fg >= fr && fr >= fb {
+                    ((0, 1, 0), (1, 1, 0))
+                } else if fg >= fb && fb >= fr {
+                    ((0, 1, 0), (0, 1, 1))
+                } else {
+                    ((0, 0, 1), (0, 1, 1))
+                };
+
+                let mut weights = [fr, fg, fb];
+                weights.sort_by(|a, b| b.total_cmp(a));
+                let [high, mid, low] = weights;
+
+                let c0 = corner(0, 0, 0);
+                let c1 = corner(first.0, first.1, first.2);
+                let c2 = corner(second.0, second.1, second.2);
+                let c3 = corner(1, 1, 1);
+
+                [0, 1, 2].map(|channel| {
+                    (1.0 - high) * c0[channel]
+                        + (high - mid) * c1[channel]
+                        + (mid - low) * c2[channel]
+                        + low * c3[channel]
+                })
+            }
+        }
+    }
+}
+
+fn triple<'a>(mut words: impl Iterator<Item = &'a str>) -> Option<[f32; 3]> {
+    let vals = [words.next()?, words.next()?, words.next()?].map(|word| word.parse::<f32>());
+    if words.next().is_some() {
+        return None;
+    }
+
+    let [r, g, b] = vals;
+    Some([r.ok()?, g.ok()?, b.ok()?])
+}
+
+#[derive(Debug, Clone)]
+pub struct LutData {
+    pub lut: Arc<CubeLut>,
+    pub interpolation: LutInterpolation,
+    // Mix between the original and the graded colour
+    pub strength: f32,
+}
+
+impl LutData {
+    pub fn create(lut: CubeLut) -> LutData {
+        LutData {
+            lut: Arc::new(lut),
+            interpolation: LutInterpolation::Tetrahedral,
+            strength: 1.0,
+        }
+    }
+
+    pub fn interpolation(mut self, interpolation: LutInterpolation) -> LutData {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn strength(mut self, strength: f32) -> LutData {
+        assert!(
+            (0.0..=1.0).contains(&strength),
+            "Strength must be between 0 and 1"
+        );
+        self.strength = strength;
+        self
+    }
+}
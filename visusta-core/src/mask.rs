@@ -0,0 +1,82 @@
+use crate::border::BorderMode;
+
+// Turns luminance into mask coverage, a linear ramp from `low` (none) to `high` (full)
+#[derive(Debug, Clone, Copy)]
+pub struct LuminanceMask {
+    pub low: u8,
+    pub high: u8,
+    pub invert: bool,
+}
+
+impl LuminanceMask {
+    pub fn create() -> LuminanceMask {
+        LuminanceMask {
+            low: 0,
+            high: 255,
+            invert: false,
+        }
+    }
+
+    // Equal bounds give a hard cut at that value
+    pub fn range(mut self, low: u8, high: u8) -> LuminanceMask {
+        assert!(low <= high, "Low must not be above high");
+        self.low = low;
+        self.high = high;
+        self
+    }
+
+    pub fn invert(mut self) -> LuminanceMask {
+        self.invert = true;
+        self
+    }
+
+    pub fn coverage(&self, luminance: u8) -> f32 {
+        let val = if luminance >= self.high {
+            1.0
+        } else if luminance <= self.low {
+            0.0
+        } else {
+            (luminance - self.low) as f32 / (self.high - self.low) as f32
+        };
+
+        if self.invert { 1.0 - val } else { val }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskMode {
+    // Pixels survive where the mask is set
+    Keep,
+    // Pixels are cut out where the mask is set
+    Remove,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MaskData {
+    pub mode: MaskMode,
+    // Blur sigma applied to the mask before it is used, 0 keeps hard edges
+    pub feather: f32,
+    // How the feather blur reads past the edge
+    pub border: BorderMode,
+}
+
+impl MaskData {
+    pub fn create(mode: MaskMode) -> MaskData {
+        MaskData {
+            mode,
+            feather: 0.0,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn feather(mut self, sigma: f32) -> MaskData {
+        assert!(sigma >= 0.0, "Feather sigma must not be negative");
+        self.feather = sigma;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> MaskData {
+        self.border = border;
+        self
+    }
+}
@@ -0,0 +1,62 @@
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuringShape {
+    Square,
+    Cross,
+    Disk,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StructuringElement {
+    pub shape: StructuringShape,
+    pub radius: u32,
+    pub border: BorderMode,
+}
+
+impl StructuringElement {
+    pub fn create(shape: StructuringShape, radius: u32) -> StructuringElement {
+        assert!(radius > 0, "Structuring element radius must be positive");
+        StructuringElement {
+            shape,
+            radius,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn border(mut self, border: BorderMode) -> StructuringElement {
+        self.border = border;
+        self
+    }
+
+    // Offsets from the centre covered by the element, the centre included
+    pub fn offsets(&self) -> Vec<(i32, i32)> {
+        let radius = self.radius as i32;
+
+        // Half a pixel past the radius keeps small disks from turning into diamonds
+        let disk_limit = (self.radius as f32 + 0.5).powi(2);
+
+        (-radius..=radius)
+            .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+            .filter(|(dx, dy)| match self.shape {
+                StructuringShape::Square => true,
+                StructuringShape::Cross => *dx == 0 || *dy == 0,
+                StructuringShape::Disk => ((dx * dx + dy * dy) as f32) <= disk_limit,
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MorphologyOp {
+    Dilate(StructuringElement),
+    Erode(StructuringElement),
+    // Erode then dilate, removes specks smaller than the element
+    Open(StructuringElement),
+    // Dilate then erode, fills gaps smaller than the element
+    Close(StructuringElement),
+    // Dilation minus erosion, leaves the outline of each shape
+    Gradient(StructuringElement),
+    // Zhang-Suen thinning down to one pixel wide lines
+    Skeletonize,
+}
@@ -0,0 +1,244 @@
+use std::{fmt, path::Path, sync::Arc};
+
+use image::RgbaImage;
+
+use crate::colour::rgb_to_lab;
+
+// Bins per Lab axis for the histogram matching, 4 * 4 * 4 bins in total
+pub const LAB_BINS: usize = 4;
+
+#[derive(Debug)]
+pub enum TileSetError {
+    Io(std::io::Error),
+    Image(image::ImageError),
+    Empty,
+    SizeMismatch {
+        name: String,
+        expected: (u32, u32),
+        got: (u32, u32),
+    },
+}
+
+impl fmt::Display for TileSetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileSetError::Io(err) => write!(f, "Failed to read tile directory: {err}"),
+            TileSetError::Image(err) => write!(f, "Failed to decode tile: {err}"),
+            TileSetError::Empty => write!(f, "Tile directory has no PNG tiles"),
+            TileSetError::SizeMismatch {
+                name,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Tile {name} is {}x{}, expected {}x{}",
+                got.0, got.1, expected.0, expected.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TileSetError {}
+
+impl From<std::io::Error> for TileSetError {
+    fn from(err: std::io::Error) -> Self {
+        TileSetError::Io(err)
+    }
+}
+
+impl From<image::ImageError> for TileSetError {
+    fn from(err: image::ImageError) -> Self {
+        TileSetError::Image(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Tile {
+    pub name: String,
+    pub image: RgbaImage,
+    pub mean_lab: [f32; 3],
+    pub histogram: Vec<f32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TileSet {
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tiles: Vec<Tile>,
+}
+
+impl TileSet {
+    // Every PNG in the directory is a tile, named by its file stem
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<TileSet, TileSetError> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+            })
+            .collect();
+
+        // Directory order isn't stable, sorting keeps tie breaks deterministic
+        paths.sort();
+
+        let mut tiles = Vec::with_capacity(paths.len());
+
+        for path in paths {
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+
+            tiles.push((name, image::open(&path)?.to_rgba8()));
+        }
+
+        TileSet::from_images(tiles)
+    }
+
+    pub fn from_images(images: Vec<(String, RgbaImage)>) -> Result<TileSet, TileSetError> {
+        let Some((_, first)) = images.first() else {
+            return Err(TileSetError::Empty);
+        };
+
+        let expected = first.dimensions();
+
+        let mut tiles = Vec::with_capacity(images.len());
+
+        for (name, image) in images {
+            if image.dimensions() != expected {
+                return Err(TileSetError::SizeMismatch {
+                    name,
+                    expected,
+                    got: image.dimensions(),
+                });
+            }
+
+            let features = ColourFeatures::from_pixels(image.pixels().map(|pixel| pixel.0));
+
+            tiles.push(Tile {
+                name,
+                image,
+                mean_lab: features.mean_lab,
+                histogram: features.histogram,
+            });
+        }
+
+        Ok(TileSet {
+            tile_width: expected.0,
+            tile_height: expected.1,
+            tiles,
+        })
+    }
+}
+
+pub struct ColourFeatures {
+    pub mean_lab: [f32; 3],
+    pub histogram: Vec<f32>,
+}
+
+impl ColourFeatures {
+    // Transparent pixels are skipped, an empty set has no histogram mass
+    pub fn from_pixels(pixels: impl Iterator<Item = [u8; 4]>) -> ColourFeatures {
+        let mut mean_lab = [0f32; 3];
+        let mut histogram = vec![0f32; LAB_BINS * LAB_BINS * LAB_BINS];
+        let mut count = 0f32;
+
+        for pixel in pixels.filter(|pixel| pixel[3] > 0) {
+            let lab = rgb_to_lab([pixel[0], pixel[1], pixel[2]]);
+
+            for (mean, val) in mean_lab.iter_mut().zip(lab) {
+                *mean += val;
+            }
+
+            add_to_histogram(&mut histogram, lab);
+            count += 1.0;
+        }
+
+        if count > 0.0 {
+            mean_lab.iter_mut().for_each(|val| *val /= count);
+            histogram.iter_mut().for_each(|val| *val /= count);
+        }
+
+        ColourFeatures {
+            mean_lab,
+            histogram,
+        }
+    }
+}
+
+// Each pixel is split across the neighbouring bins on every axis,
+//  so near identical colours either side of a bin edge still overlap
+fn add_to_histogram(histogram: &mut [f32], lab: [f32; 3]) {
+    let split = |val: f32, min: f32, max: f32| {
+        let pos =
+            (((val - min) / (max - min)) * LAB_BINS as f32 - 0.5).clamp(0.0, LAB_BINS as f32 - 1.0);
+        let low = pos.floor() as usize;
+        let high = (low + 1).min(LAB_BINS - 1);
+        let weight = pos - low as f32;
+        [(low, 1.0 - weight), (high, weight)]
+    };
+
+    let l = split(lab[0], 0.0, 100.0);
+    let a = split(lab[1], -80.0, 80.0);
+    let b = split(lab[2], -80.0, 80.0);
+
+    for (l_bin, l_weight) in l {
+        for (a_bin, a_weight) in a {
+            for (b_bin, b_weight) in b {
+                histogram[(l_bin * LAB_BINS + a_bin) * LAB_BINS + b_bin] +=
+                    l_weight * a_weight * b_weight;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TileMatching {
+    // Closest mean colour by Lab distance
+    MeanColour,
+    // Closest Lab histogram by intersection
+    Histogram,
+}
+
+// The tile name grid is only available through VisustaProcessor::mosaic_rgba_grid,
+//  a pipeline step only carries the composited image
+#[derive(Debug, Clone)]
+pub struct MosaicData {
+    pub tiles: Arc<TileSet>,
+    pub block_size: u32,
+    pub matching: TileMatching,
+    // Once a tile has been placed this many times the next best tile is used
+    pub max_repeats: Option<u32>,
+}
+
+impl MosaicData {
+    pub fn create(tiles: Arc<TileSet>, block_size: u32) -> MosaicData {
+        assert!(block_size > 0, "Block size must be positive");
+
+        MosaicData {
+            tiles,
+            block_size,
+            matching: TileMatching::MeanColour,
+            max_repeats: None,
+        }
+    }
+
+    pub fn matching(mut self, matching: TileMatching) -> MosaicData {
+        self.matching = matching;
+        self
+    }
+
+    pub fn max_repeats(mut self, max_repeats: u32) -> MosaicData {
+        assert!(max_repeats > 0, "Tiles must be usable at least once");
+        self.max_repeats = Some(max_repeats);
+        self
+    }
+}
+
+// Tile chosen per block in row order, None where the block was fully transparent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileGrid {
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<Option<String>>,
+}
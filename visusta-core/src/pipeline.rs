@@ -1,9 +1,34 @@
-use image::RgbaImage;
+use std::borrow::Cow;
+
+use image::{Rgba32FImage, RgbaImage};
 
 use crate::{
-    CharImage, LumaAImage, LuminanceAsciiFilter, LuminanceFilter, SobelAscii, SobelColorData,
-    VisustaProcessor,
-    gaussians::{GaussianBuilder, GaussianColorData},
+    CharImage, GradientAsciiData, GradientColourData, LumaA32FImage, LumaAImage,
+    LuminanceAsciiFilter, LuminanceFilter, SobelAscii, SobelColorData, VisustaProcessor,
+    auxiliary::{AuxiliaryImage, BlendData},
+    canny::{CannyData, CannyThresholds},
+    channel::ChannelMapping,
+    effects::{BloomData, GrainData, VignetteData},
+    float::FloatRange,
+    gaussians::DogOutput,
+    gaussians::{BlurData, GaussianBuilder},
+    grade::ColourGrade,
+    gradient::{GradientData, GradientField, GradientMagnitude},
+    gradient_map::GradientMap,
+    halftone::{CmykHalftoneData, HalftoneData},
+    hatch::HatchData,
+    histogram::HistogramFilter,
+    kuwahara::KuwaharaData,
+    lowpoly::LowPolyData,
+    lut::LutData,
+    mask::{LuminanceMask, MaskData},
+    morphology::MorphologyOp,
+    mosaic::MosaicData,
+    pixelsort::PixelSortData,
+    threshold::{PosterizeData, ThresholdFilter},
+    tone::ToneFilter,
+    voronoi::{StainedGlassData, StippleData},
+    xdog::XDogData,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -11,6 +36,9 @@ pub enum DataType {
     Rgba,
     LumaA,
     Char,
+    Gradient,
+    Rgba32F,
+    Luma32F,
 }
 
 #[derive(Debug)]
@@ -19,13 +47,35 @@ pub struct PipelineError {
     pub location: PipelineLocation,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PipelineErrorKind {
-    TypeMismatch { expected: DataType, got: DataType },
-    LayerOutputMismatch { expected: DataType, got: DataType },
-    ExecutionMismatch { expected: DataType, got: DataType },
+    TypeMismatch {
+        expected: DataType,
+        got: DataType,
+    },
+    LayerOutputMismatch {
+        expected: DataType,
+        got: DataType,
+    },
+    ExecutionMismatch {
+        expected: DataType,
+        got: DataType,
+    },
     EmptyLayer,
     EmptyPipeline,
+    // A step read from a layer that doesn't run before it
+    LayerReference {
+        layer: usize,
+    },
+    // A step read from an auxiliary input the pipeline doesn't declare
+    UnknownInput {
+        name: String,
+    },
+    // A step's second image isn't the size of the one passed down the layer
+    SizeMismatch {
+        expected: (u32, u32),
+        got: (u32, u32),
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -35,50 +85,232 @@ pub enum PipelineLocation {
     Step { layer: usize, step: usize },
 }
 
+// Where a step reads an input from besides the one passed down the layer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepInput {
+    // The image the pipeline was run on
+    Source,
+    // The output of an earlier layer
+    Layer(usize),
+    // An auxiliary image declared on the pipeline, fitted to the size of the step reading it
+    Auxiliary(String),
+}
+
 #[derive(Debug, Clone)]
 pub enum ProcessingStep {
+    // RgbaImage -> RgbaImage
+    ToneRgba(ToneFilter),
+    GaussianBlurRgba(BlurData),
+    Kuwahara(KuwaharaData),
+    FilmGrain(GrainData),
+    Vignette(VignetteData),
+    Bloom(BloomData),
+    // Only the composited image, the tile name grid isn't an image so it is only
+    //  returned by VisustaProcessor::mosaic_rgba_grid
+    Mosaic(MosaicData),
+    LowPoly(LowPolyData),
+    StainedGlass(StainedGlassData),
+    Stipple(StippleData),
+    HalftoneCmyk(CmykHalftoneData),
+    PixelSort(PixelSortData),
+    PosterizeRgba(PosterizeData),
+    ApplyLut(LutData),
+    Grade(ColourGrade),
+
     // RgbaImage -> LumaAImage
     ToLuminance(LuminanceFilter),
+    Halftone(HalftoneData),
+    // The optional input is a paper texture, its luminance is multiplied under the strokes
+    HatchRgba(HatchData, Option<StepInput>),
 
     // LumaAImage -> RgbaImage
     LumaToRgba,
-    SobelToColour(SobelColorData),
-    GaussianToColoured(GaussianBuilder, GaussianColorData),
+    // The optional input backs ChannelSource::Original, grey from luminance without one
+    SobelToColour(SobelColorData, Option<StepInput>),
+    GaussianToColoured(GaussianBuilder, ChannelMapping, Option<StepInput>),
+    GradientMap(GradientMap),
 
     // LumaAImage -> LumaAImage
     GaussianOnLuma(GaussianBuilder),
+    ToneLuma(ToneFilter),
+    HistogramLuma(HistogramFilter),
+    Canny(CannyData),
+    GaussianBlurLuma(BlurData),
+    GaussianDifference(GaussianBuilder, DogOutput),
+    XDog(XDogData),
+    HatchLuma(HatchData, Option<StepInput>),
+    Threshold(ThresholdFilter),
+    Morphology(MorphologyOp),
+    LuminanceMask(LuminanceMask),
+
+    // LumaAImage + RgbaImage -> RgbaImage
+    ApplyMask(MaskData, StepInput),
+
+    // RgbaImage + RgbaImage -> RgbaImage
+    Blend(BlendData, StepInput),
 
     // LumaAImage -> CharImage
     LuminanceToAscii(LuminanceAsciiFilter),
     SobelAsciiDirectional(SobelAscii),
+
+    // LumaAImage -> GradientField
+    Gradient(GradientData),
+
+    // GradientField -> RgbaImage
+    GradientToColour(GradientColourData, Option<StepInput>),
+
+    // GradientField -> LumaAImage
+    GradientToLuma(GradientMagnitude),
+    GradientHatch(HatchData, Option<StepInput>),
+    GradientEdges(CannyThresholds),
+
+    // GradientField -> CharImage
+    GradientToAscii(GradientAsciiData),
+
+    // RgbaImage <-> Rgba32FImage
+    RgbaToFloat,
+    RgbaFromFloat(FloatRange),
+
+    // LumaAImage <-> LumaA32FImage
+    LumaToFloat,
+    LumaFromFloat(FloatRange),
+
+    // Rgba32FImage -> Rgba32FImage
+    GaussianBlurRgba32F(BlurData),
+
+    // Rgba32FImage -> LumaA32FImage
+    ToLuminance32F(LuminanceFilter),
+
+    // LumaA32FImage -> LumaA32FImage
+    GaussianBlurLuma32F(BlurData),
+    GaussianDifference32F(GaussianBuilder),
+
+    // LumaA32FImage -> GradientField
+    Gradient32F(GradientData),
 }
 
 impl ProcessingStep {
     fn signature(&self) -> (DataType, DataType) {
         match self {
+            // RgbaImage -> RgbaImage
+            ProcessingStep::ToneRgba(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::GaussianBlurRgba(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::Kuwahara(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::FilmGrain(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::Vignette(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::Bloom(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::Mosaic(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::LowPoly(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::StainedGlass(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::Stipple(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::HalftoneCmyk(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::PixelSort(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::PosterizeRgba(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::ApplyLut(_) => (DataType::Rgba, DataType::Rgba),
+            ProcessingStep::Grade(_) => (DataType::Rgba, DataType::Rgba),
+
             // RgbaImage -> LumaAImage
             ProcessingStep::ToLuminance(_) => (DataType::Rgba, DataType::LumaA),
+            ProcessingStep::Halftone(_) => (DataType::Rgba, DataType::LumaA),
+            ProcessingStep::HatchRgba(_, _) => (DataType::Rgba, DataType::LumaA),
 
             // LumaAImage -> RgbaImage
             ProcessingStep::LumaToRgba => (DataType::LumaA, DataType::Rgba),
-            ProcessingStep::SobelToColour(_) => (DataType::LumaA, DataType::Rgba),
-            ProcessingStep::GaussianToColoured(_, _) => (DataType::LumaA, DataType::Rgba),
+            ProcessingStep::SobelToColour(_, _) => (DataType::LumaA, DataType::Rgba),
+            ProcessingStep::GaussianToColoured(_, _, _) => (DataType::LumaA, DataType::Rgba),
+            ProcessingStep::GradientMap(_) => (DataType::LumaA, DataType::Rgba),
 
             // LumaAImage -> LumaAImage
             ProcessingStep::GaussianOnLuma(_) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::ToneLuma(_) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::HistogramLuma(_) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::Canny(_) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::GaussianBlurLuma(_) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::GaussianDifference(_, _) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::XDog(_) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::HatchLuma(_, _) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::Threshold(_) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::Morphology(_) => (DataType::LumaA, DataType::LumaA),
+            ProcessingStep::LuminanceMask(_) => (DataType::LumaA, DataType::LumaA),
+
+            // LumaAImage + RgbaImage -> RgbaImage
+            ProcessingStep::ApplyMask(_, _) => (DataType::LumaA, DataType::Rgba),
+
+            // RgbaImage + RgbaImage -> RgbaImage
+            ProcessingStep::Blend(_, _) => (DataType::Rgba, DataType::Rgba),
 
             // LumaAImage -> CharImage
             ProcessingStep::LuminanceToAscii(_) => (DataType::LumaA, DataType::Char),
             ProcessingStep::SobelAsciiDirectional(_) => (DataType::LumaA, DataType::Char),
+
+            // LumaAImage -> GradientField
+            ProcessingStep::Gradient(_) => (DataType::LumaA, DataType::Gradient),
+
+            // GradientField -> RgbaImage
+            ProcessingStep::GradientToColour(_, _) => (DataType::Gradient, DataType::Rgba),
+
+            // GradientField -> LumaAImage
+            ProcessingStep::GradientToLuma(_) => (DataType::Gradient, DataType::LumaA),
+            ProcessingStep::GradientHatch(_, _) => (DataType::Gradient, DataType::LumaA),
+            ProcessingStep::GradientEdges(_) => (DataType::Gradient, DataType::LumaA),
+
+            // GradientField -> CharImage
+            ProcessingStep::GradientToAscii(_) => (DataType::Gradient, DataType::Char),
+
+            // RgbaImage <-> Rgba32FImage
+            ProcessingStep::RgbaToFloat => (DataType::Rgba, DataType::Rgba32F),
+            ProcessingStep::RgbaFromFloat(_) => (DataType::Rgba32F, DataType::Rgba),
+
+            // LumaAImage <-> LumaA32FImage
+            ProcessingStep::LumaToFloat => (DataType::LumaA, DataType::Luma32F),
+            ProcessingStep::LumaFromFloat(_) => (DataType::Luma32F, DataType::LumaA),
+
+            // Rgba32FImage -> Rgba32FImage
+            ProcessingStep::GaussianBlurRgba32F(_) => (DataType::Rgba32F, DataType::Rgba32F),
+
+            // Rgba32FImage -> LumaA32FImage
+            ProcessingStep::ToLuminance32F(_) => (DataType::Rgba32F, DataType::Luma32F),
+
+            // LumaA32FImage -> LumaA32FImage
+            ProcessingStep::GaussianBlurLuma32F(_) => (DataType::Luma32F, DataType::Luma32F),
+            ProcessingStep::GaussianDifference32F(_) => (DataType::Luma32F, DataType::Luma32F),
+
+            // LumaA32FImage -> GradientField
+            ProcessingStep::Gradient32F(_) => (DataType::Luma32F, DataType::Gradient),
+        }
+    }
+
+    // The inputs a step reads besides the one passed down the layer
+    fn inputs(&self) -> Vec<(&StepInput, DataType)> {
+        match self {
+            ProcessingStep::ApplyMask(_, target) => vec![(target, DataType::Rgba)],
+            ProcessingStep::Blend(_, top) => vec![(top, DataType::Rgba)],
+            ProcessingStep::SobelToColour(_, original)
+            | ProcessingStep::GaussianToColoured(_, _, original)
+            | ProcessingStep::GradientToColour(_, original) => original
+                .iter()
+                .map(|original| (original, DataType::Rgba))
+                .collect(),
+            ProcessingStep::HatchRgba(_, paper)
+            | ProcessingStep::HatchLuma(_, paper)
+            | ProcessingStep::GradientHatch(_, paper) => {
+                paper.iter().map(|paper| (paper, DataType::Rgba)).collect()
+            }
+            _ => Vec::new(),
         }
     }
 
     async fn execute(
         &self,
         input: LayerOutput,
+        inputs: &StepInputs<'_>,
         processor: &dyn VisustaProcessor,
     ) -> Result<LayerOutput, PipelineErrorKind> {
         let output = match self {
+            ProcessingStep::ToneRgba(filter) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.tone_rgba(&img, filter.clone()).await)
+            }
             ProcessingStep::ToLuminance(filter) => {
                 let img = input.into_rgba()?;
                 LayerOutput::LumaA(processor.rgba_to_luma_a(&img, *filter).await)
@@ -87,22 +319,45 @@ impl ProcessingStep {
                 let img = input.into_luma()?;
                 LayerOutput::Rgba(processor.luma_to_rgba(&img).await)
             }
-            ProcessingStep::SobelToColour(filter) => {
+            ProcessingStep::SobelToColour(filter, original) => {
                 let img = input.into_luma()?;
-                LayerOutput::Rgba(processor.sobel_to_colour(&img, filter.clone()).await)
+                let original = inputs
+                    .get_optional_rgba(original.as_ref(), img.dimensions())
+                    .await?;
+                LayerOutput::Rgba(
+                    processor
+                        .sobel_to_colour(&img, filter.clone(), original.as_deref())
+                        .await,
+                )
             }
-            ProcessingStep::GaussianToColoured(builder, filter) => {
+            ProcessingStep::GaussianToColoured(builder, channels, original) => {
                 let img = input.into_luma()?;
+                let original = inputs
+                    .get_optional_rgba(original.as_ref(), img.dimensions())
+                    .await?;
                 LayerOutput::Rgba(
                     processor
-                        .gaussian_to_coloured(&img, builder.clone(), filter.clone())
+                        .gaussian_to_coloured(
+                            &img,
+                            builder.clone(),
+                            channels.clone(),
+                            original.as_deref(),
+                        )
                         .await,
                 )
             }
+            ProcessingStep::GradientMap(map) => {
+                let img = input.into_luma()?;
+                LayerOutput::Rgba(processor.gradient_map(&img, map.clone()).await)
+            }
             ProcessingStep::GaussianOnLuma(builder) => {
                 let img = input.into_luma()?;
                 LayerOutput::LumaA(processor.gaussian_on_luma(&img, builder.clone()).await)
             }
+            ProcessingStep::ToneLuma(filter) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.tone_luma(&img, filter.clone()).await)
+            }
             ProcessingStep::LuminanceToAscii(filter) => {
                 let img = input.into_luma()?;
                 LayerOutput::Char(processor.luminance_to_ascii(&img, filter.clone()).await)
@@ -115,6 +370,202 @@ impl ProcessingStep {
                         .await,
                 )
             }
+            ProcessingStep::HistogramLuma(filter) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.histogram_luma(&img, *filter).await)
+            }
+            ProcessingStep::Canny(data) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.canny_on_luma(&img, *data).await)
+            }
+            ProcessingStep::GaussianBlurRgba(blur) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.blur_rgba(&img, blur.sigma, blur.border).await)
+            }
+            ProcessingStep::GaussianBlurLuma(blur) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.blur_luma(&img, blur.sigma, blur.border).await)
+            }
+            ProcessingStep::GaussianDifference(builder, output) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.dog_on_luma(&img, builder.clone(), *output).await)
+            }
+            ProcessingStep::XDog(data) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.xdog_on_luma(&img, *data).await)
+            }
+            ProcessingStep::Kuwahara(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.kuwahara_rgba(&img, *data).await)
+            }
+            ProcessingStep::FilmGrain(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.grain_rgba(&img, *data).await)
+            }
+            ProcessingStep::Vignette(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.vignette_rgba(&img, *data).await)
+            }
+            ProcessingStep::Bloom(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.bloom_rgba(&img, *data).await)
+            }
+            ProcessingStep::Mosaic(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.mosaic_rgba(&img, data.clone()).await)
+            }
+            ProcessingStep::LowPoly(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.low_poly_rgba(&img, *data).await)
+            }
+            ProcessingStep::StainedGlass(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.stained_glass_rgba(&img, *data).await)
+            }
+            ProcessingStep::Stipple(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.stipple_rgba(&img, *data).await)
+            }
+            ProcessingStep::HalftoneCmyk(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.halftone_cmyk(&img, *data).await)
+            }
+            ProcessingStep::Halftone(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::LumaA(processor.halftone_rgba(&img, *data).await)
+            }
+            ProcessingStep::PixelSort(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.pixel_sort_rgba(&img, *data).await)
+            }
+            ProcessingStep::HatchRgba(data, paper) => {
+                let img = input.into_rgba()?;
+                let paper = inputs
+                    .get_optional_rgba(paper.as_ref(), img.dimensions())
+                    .await?;
+                LayerOutput::LumaA(
+                    processor
+                        .hatch_rgba(&img, data.clone(), paper.as_deref())
+                        .await,
+                )
+            }
+            ProcessingStep::HatchLuma(data, paper) => {
+                let img = input.into_luma()?;
+                let paper = inputs
+                    .get_optional_rgba(paper.as_ref(), img.dimensions())
+                    .await?;
+                LayerOutput::LumaA(
+                    processor
+                        .hatch_luma(&img, data.clone(), paper.as_deref())
+                        .await,
+                )
+            }
+            ProcessingStep::PosterizeRgba(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.posterize_rgba(&img, *data).await)
+            }
+            ProcessingStep::ApplyLut(data) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.lut_rgba(&img, data.clone()).await)
+            }
+            ProcessingStep::Grade(grade) => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba(processor.grade_rgba(&img, *grade).await)
+            }
+            ProcessingStep::Threshold(filter) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.threshold_luma(&img, *filter).await)
+            }
+            ProcessingStep::Morphology(op) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.morphology_luma(&img, *op).await)
+            }
+            ProcessingStep::LuminanceMask(data) => {
+                let img = input.into_luma()?;
+                LayerOutput::LumaA(processor.luminance_mask(&img, *data).await)
+            }
+            ProcessingStep::ApplyMask(data, target) => {
+                let mask = input.into_luma()?;
+                let img = inputs.get_rgba(target, mask.dimensions()).await?;
+                LayerOutput::Rgba(processor.apply_mask(&img, &mask, *data).await)
+            }
+            ProcessingStep::Blend(data, top) => {
+                let img = input.into_rgba()?;
+                let top = inputs.get_rgba(top, img.dimensions()).await?;
+                LayerOutput::Rgba(processor.blend_rgba(&img, &top, *data).await)
+            }
+            ProcessingStep::Gradient(data) => {
+                let img = input.into_luma()?;
+                LayerOutput::Gradient(processor.gradient_luma(&img, *data).await)
+            }
+            ProcessingStep::GradientToColour(filter, original) => {
+                let field = input.into_gradient()?;
+                let size = (field.width as u32, field.height as u32);
+                let original = inputs.get_optional_rgba(original.as_ref(), size).await?;
+                LayerOutput::Rgba(
+                    processor
+                        .gradient_to_colour(&field, filter.clone(), original.as_deref())
+                        .await,
+                )
+            }
+            ProcessingStep::GradientToLuma(measure) => {
+                let field = input.into_gradient()?;
+                LayerOutput::LumaA(processor.gradient_to_luma(&field, *measure).await)
+            }
+            ProcessingStep::GradientHatch(data, paper) => {
+                let field = input.into_gradient()?;
+                let size = (field.width as u32, field.height as u32);
+                let paper = inputs.get_optional_rgba(paper.as_ref(), size).await?;
+                LayerOutput::LumaA(
+                    processor
+                        .gradient_hatch(&field, data.clone(), paper.as_deref())
+                        .await,
+                )
+            }
+            ProcessingStep::GradientEdges(thresholds) => {
+                let field = input.into_gradient()?;
+                LayerOutput::LumaA(processor.gradient_edges(&field, *thresholds).await)
+            }
+            ProcessingStep::GradientToAscii(filter) => {
+                let field = input.into_gradient()?;
+                LayerOutput::Char(processor.gradient_to_ascii(&field, filter.clone()).await)
+            }
+            ProcessingStep::RgbaToFloat => {
+                let img = input.into_rgba()?;
+                LayerOutput::Rgba32F(processor.rgba_to_float(&img).await)
+            }
+            ProcessingStep::RgbaFromFloat(range) => {
+                let img = input.into_rgba_f32()?;
+                LayerOutput::Rgba(processor.rgba_from_float(&img, *range).await)
+            }
+            ProcessingStep::LumaToFloat => {
+                let img = input.into_luma()?;
+                LayerOutput::Luma32F(processor.luma_to_float(&img).await)
+            }
+            ProcessingStep::LumaFromFloat(range) => {
+                let img = input.into_luma_f32()?;
+                LayerOutput::LumaA(processor.luma_from_float(&img, *range).await)
+            }
+            ProcessingStep::GaussianBlurRgba32F(blur) => {
+                let img = input.into_rgba_f32()?;
+                LayerOutput::Rgba32F(processor.blur_rgba_f32(&img, blur.sigma, blur.border).await)
+            }
+            ProcessingStep::ToLuminance32F(filter) => {
+                let img = input.into_rgba_f32()?;
+                LayerOutput::Luma32F(processor.rgba_to_luma_f32(&img, *filter).await)
+            }
+            ProcessingStep::GaussianBlurLuma32F(blur) => {
+                let img = input.into_luma_f32()?;
+                LayerOutput::Luma32F(processor.blur_luma_f32(&img, blur.sigma, blur.border).await)
+            }
+            ProcessingStep::GaussianDifference32F(builder) => {
+                let img = input.into_luma_f32()?;
+                LayerOutput::Luma32F(processor.dog_on_luma_f32(&img, builder.clone()).await)
+            }
+            ProcessingStep::Gradient32F(data) => {
+                let img = input.into_luma_f32()?;
+                LayerOutput::Gradient(processor.gradient_luma_f32(&img, *data).await)
+            }
         };
 
         Ok(output)
@@ -127,6 +578,12 @@ pub struct Layer {
     output_type: DataType,
 }
 
+impl Default for Layer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Layer {
     pub fn new() -> Self {
         Layer {
@@ -144,7 +601,7 @@ impl Layer {
         self
     }
 
-    fn validate(&self, self_idx: usize) -> Result<DataType, PipelineError> {
+    fn validate(&self, self_idx: usize, types: &InputTypes<'_>) -> Result<DataType, PipelineError> {
         let mut steps_iter = self.steps.iter();
 
         let Some(prev) = steps_iter.next() else {
@@ -154,12 +611,32 @@ impl Layer {
             });
         };
 
+        for (step, current) in self.steps.iter().enumerate() {
+            for (step_input, expected) in current.inputs() {
+                let location = PipelineLocation::Step {
+                    layer: self_idx,
+                    step,
+                };
+
+                let got = types
+                    .get(step_input)
+                    .map_err(|kind| PipelineError { kind, location })?;
+
+                if got != expected {
+                    return Err(PipelineError {
+                        kind: PipelineErrorKind::TypeMismatch { expected, got },
+                        location,
+                    });
+                }
+            }
+        }
+
         let (prev_input, mut out) = prev.signature();
 
-        if prev_input != DataType::Rgba {
+        if prev_input != types.source {
             return Err(PipelineError {
                 kind: PipelineErrorKind::TypeMismatch {
-                    expected: DataType::Rgba,
+                    expected: types.source,
                     got: prev_input,
                 },
                 location: PipelineLocation::Step {
@@ -194,13 +671,14 @@ impl Layer {
     async fn execute(
         &self,
         processor: &dyn VisustaProcessor,
-        img: &RgbaImage,
+        input: LayerOutput,
+        inputs: &StepInputs<'_>,
     ) -> Result<LayerOutput, (PipelineErrorKind, usize)> {
-        let mut current = LayerOutput::Rgba(img.clone());
+        let mut current = input;
 
         for (step_index, step) in self.steps.iter().enumerate() {
             current = step
-                .execute(current, processor)
+                .execute(current, inputs, processor)
                 .await
                 .map_err(|kind| (kind, step_index))?;
         }
@@ -212,11 +690,15 @@ impl Layer {
 #[derive(Debug, Clone, Default)]
 pub struct Pipeline {
     layers: Vec<Layer>,
+    inputs: Vec<(String, AuxiliaryImage)>,
 }
 
 impl Pipeline {
     pub fn new() -> Self {
-        Pipeline { layers: Vec::new() }
+        Pipeline {
+            layers: Vec::new(),
+            inputs: Vec::new(),
+        }
     }
 
     pub fn add_layer(mut self, layer: Layer) -> Self {
@@ -224,7 +706,18 @@ impl Pipeline {
         self
     }
 
+    // Declaring a name twice replaces the earlier image
+    pub fn add_input(mut self, name: &str, input: AuxiliaryImage) -> Self {
+        self.inputs.retain(|(existing, _)| existing != name);
+        self.inputs.push((name.to_string(), input));
+        self
+    }
+
     pub fn validate(&self) -> Result<DataType, PipelineError> {
+        self.validate_from(DataType::Rgba)
+    }
+
+    fn validate_from(&self, input: DataType) -> Result<DataType, PipelineError> {
         let mut layers = self.layers.iter();
 
         let Some(prev) = layers.next() else {
@@ -234,13 +727,28 @@ impl Pipeline {
             });
         };
 
-        let expected_out = prev.validate(0)?;
+        let auxiliary: Vec<&str> = self.inputs.iter().map(|(name, _)| name.as_str()).collect();
+
+        let expected_out = prev.validate(
+            0,
+            &InputTypes {
+                source: input,
+                layers: &[],
+                auxiliary: &auxiliary,
+            },
+        )?;
+        let mut outputs = vec![expected_out];
 
         let mut layer_idx = 0;
 
-        while let Some(layer) = layers.next() {
+        for layer in layers {
             layer_idx += 1;
-            let out = layer.validate(layer_idx)?;
+            let types = InputTypes {
+                source: input,
+                layers: &outputs,
+                auxiliary: &auxiliary,
+            };
+            let out = layer.validate(layer_idx, &types)?;
 
             if expected_out != out {
                 return Err(PipelineError {
@@ -251,6 +759,8 @@ impl Pipeline {
                     location: PipelineLocation::Layer { layer: layer_idx },
                 });
             }
+
+            outputs.push(out);
         }
 
         Ok(expected_out)
@@ -261,14 +771,46 @@ impl Pipeline {
         img: &RgbaImage,
         processor: &dyn VisustaProcessor,
     ) -> Result<Vec<LayerOutput>, PipelineError> {
-        self.validate()?;
+        self.execute_from(DataType::Rgba, || LayerOutput::Rgba(img.clone()), processor)
+            .await
+    }
+
+    // For high bit depth sources, every layer starts from the float image
+    pub async fn execute_float(
+        &self,
+        img: &Rgba32FImage,
+        processor: &dyn VisustaProcessor,
+    ) -> Result<Vec<LayerOutput>, PipelineError> {
+        self.execute_from(
+            DataType::Rgba32F,
+            || LayerOutput::Rgba32F(img.clone()),
+            processor,
+        )
+        .await
+    }
+
+    async fn execute_from(
+        &self,
+        input_type: DataType,
+        input: impl Fn() -> LayerOutput,
+        processor: &dyn VisustaProcessor,
+    ) -> Result<Vec<LayerOutput>, PipelineError> {
+        self.validate_from(input_type)?;
+
+        let source = input();
 
         let mut outputs = Vec::with_capacity(self.layers.len());
 
         for (layer_index, layer) in self.layers.iter().enumerate() {
-            outputs.push(
+            let inputs = StepInputs {
+                source: &source,
+                layers: &outputs,
+                auxiliary: &self.inputs,
+                processor,
+            };
+            let output =
                 layer
-                    .execute(processor, img)
+                    .execute(processor, input(), &inputs)
                     .await
                     .map_err(|(kind, step_idx)| PipelineError {
                         kind,
@@ -276,18 +818,117 @@ impl Pipeline {
                             layer: layer_index,
                             step: step_idx,
                         },
-                    })?,
-            );
+                    })?;
+            outputs.push(output);
         }
 
         Ok(outputs)
     }
 }
 
+// Everything a step can read besides its own input, for steps with several inputs
+struct StepInputs<'a> {
+    source: &'a LayerOutput,
+    layers: &'a [LayerOutput],
+    auxiliary: &'a [(String, AuxiliaryImage)],
+    // Fits auxiliary images to whatever size the reading step is working at
+    processor: &'a dyn VisustaProcessor,
+}
+
+impl StepInputs<'_> {
+    // Sizes are only known once the layer has run this far, so they are handled here
+    //  rather than in validate. Auxiliary images are fitted by their policy, the
+    //  source and earlier layers must already match
+    async fn get_rgba(
+        &self,
+        input: &StepInput,
+        size: (u32, u32),
+    ) -> Result<Cow<'_, RgbaImage>, PipelineErrorKind> {
+        let output = match input {
+            StepInput::Source => self.source,
+            StepInput::Layer(layer) => self
+                .layers
+                .get(*layer)
+                .ok_or(PipelineErrorKind::LayerReference { layer: *layer })?,
+            StepInput::Auxiliary(name) => {
+                let (_, aux) = self
+                    .auxiliary
+                    .iter()
+                    .find(|(aux, _)| aux == name)
+                    .ok_or_else(|| PipelineErrorKind::UnknownInput { name: name.clone() })?;
+
+                if aux.image.dimensions() == size {
+                    return Ok(Cow::Borrowed(&aux.image));
+                }
+
+                let fitted = self
+                    .processor
+                    .fit_rgba(&aux.image, size.0, size.1, aux.fit)
+                    .await;
+                return Ok(Cow::Owned(fitted));
+            }
+        };
+
+        let img = output.as_rgba()?;
+
+        if img.dimensions() != size {
+            return Err(PipelineErrorKind::SizeMismatch {
+                expected: size,
+                got: img.dimensions(),
+            });
+        }
+
+        Ok(Cow::Borrowed(img))
+    }
+
+    // For optional inputs, a step given none reads None
+    async fn get_optional_rgba(
+        &self,
+        input: Option<&StepInput>,
+        size: (u32, u32),
+    ) -> Result<Option<Cow<'_, RgbaImage>>, PipelineErrorKind> {
+        match input {
+            Some(input) => Ok(Some(self.get_rgba(input, size).await?)),
+            None => Ok(None),
+        }
+    }
+}
+
+// The types `StepInputs` will hold, known before anything runs
+struct InputTypes<'a> {
+    source: DataType,
+    // Outputs of the layers before the one being validated
+    layers: &'a [DataType],
+    auxiliary: &'a [&'a str],
+}
+
+impl InputTypes<'_> {
+    fn get(&self, input: &StepInput) -> Result<DataType, PipelineErrorKind> {
+        match input {
+            StepInput::Source => Ok(self.source),
+            StepInput::Layer(layer) => self
+                .layers
+                .get(*layer)
+                .copied()
+                .ok_or(PipelineErrorKind::LayerReference { layer: *layer }),
+            StepInput::Auxiliary(name) => {
+                if self.auxiliary.contains(&name.as_str()) {
+                    Ok(DataType::Rgba)
+                } else {
+                    Err(PipelineErrorKind::UnknownInput { name: name.clone() })
+                }
+            }
+        }
+    }
+}
+
 pub enum LayerOutput {
     Rgba(RgbaImage),
     LumaA(LumaAImage),
     Char(CharImage),
+    Gradient(GradientField),
+    Rgba32F(Rgba32FImage),
+    Luma32F(LumaA32FImage),
 }
 
 impl LayerOutput {
@@ -296,6 +937,20 @@ impl LayerOutput {
             LayerOutput::Rgba(_) => DataType::Rgba,
             LayerOutput::LumaA(_) => DataType::LumaA,
             LayerOutput::Char(_) => DataType::Char,
+            LayerOutput::Gradient(_) => DataType::Gradient,
+            LayerOutput::Rgba32F(_) => DataType::Rgba32F,
+            LayerOutput::Luma32F(_) => DataType::Luma32F,
+        }
+    }
+
+    pub fn dimensions(&self) -> (usize, usize) {
+        match self {
+            LayerOutput::Rgba(img) => (img.width() as usize, img.height() as usize),
+            LayerOutput::LumaA(img) => (img.width() as usize, img.height() as usize),
+            LayerOutput::Char(img) => (img.width, img.height),
+            LayerOutput::Gradient(field) => (field.width, field.height),
+            LayerOutput::Rgba32F(img) => (img.width() as usize, img.height() as usize),
+            LayerOutput::Luma32F(img) => (img.width() as usize, img.height() as usize),
         }
     }
 
@@ -309,6 +964,16 @@ impl LayerOutput {
         }
     }
 
+    pub fn as_rgba(&self) -> Result<&RgbaImage, PipelineErrorKind> {
+        match self {
+            LayerOutput::Rgba(img) => Ok(img),
+            _ => Err(PipelineErrorKind::ExecutionMismatch {
+                expected: DataType::Rgba,
+                got: self.data_type(),
+            }),
+        }
+    }
+
     pub fn into_luma(self) -> Result<LumaAImage, PipelineErrorKind> {
         match self {
             LayerOutput::LumaA(img) => Ok(img),
@@ -328,4 +993,34 @@ impl LayerOutput {
             }),
         }
     }
+
+    pub fn into_gradient(self) -> Result<GradientField, PipelineErrorKind> {
+        match self {
+            LayerOutput::Gradient(field) => Ok(field),
+            _ => Err(PipelineErrorKind::ExecutionMismatch {
+                expected: DataType::Gradient,
+                got: self.data_type(),
+            }),
+        }
+    }
+
+    pub fn into_rgba_f32(self) -> Result<Rgba32FImage, PipelineErrorKind> {
+        match self {
+            LayerOutput::Rgba32F(img) => Ok(img),
+            _ => Err(PipelineErrorKind::ExecutionMismatch {
+                expected: DataType::Rgba32F,
+                got: self.data_type(),
+            }),
+        }
+    }
+
+    pub fn into_luma_f32(self) -> Result<LumaA32FImage, PipelineErrorKind> {
+        match self {
+            LayerOutput::Luma32F(img) => Ok(img),
+            _ => Err(PipelineErrorKind::ExecutionMismatch {
+                expected: DataType::Luma32F,
+                got: self.data_type(),
+            }),
+        }
+    }
 }
@@ -0,0 +1,127 @@
+use crate::border::BorderMode;
+use crate::colour::rgb_to_hsv;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortDirection {
+    Rows,
+    Columns,
+    // Degrees clockwise from the rows, lines are walked along this angle
+    Angle(f32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Luminance,
+    Hue,
+    Saturation,
+    Red,
+    Green,
+    Blue,
+}
+
+impl SortKey {
+    // Normalised to 0 - 1 so thresholds read the same for every key
+    pub fn value(&self, pixel: [u8; 4]) -> f32 {
+        match self {
+            SortKey::Luminance => {
+                (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32)
+                    / 255.0
+            }
+            SortKey::Hue => rgb_to_hsv([pixel[0], pixel[1], pixel[2]])[0] / 360.0,
+            SortKey::Saturation => rgb_to_hsv([pixel[0], pixel[1], pixel[2]])[1],
+            SortKey::Red => pixel[0] as f32 / 255.0,
+            SortKey::Green => pixel[1] as f32 / 255.0,
+            SortKey::Blue => pixel[2] as f32 / 255.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalMode {
+    // Pixels whose key falls inside low - high are sorted, everything else breaks an interval
+    Threshold { key: SortKey, low: f32, high: f32 },
+    // Sobel magnitude over the threshold, as a fraction of the strongest possible edge, breaks an interval
+    Edges { threshold: f32 },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RandomIntervals {
+    pub max_length: u32,
+    pub seed: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PixelSortData {
+    pub direction: SortDirection,
+    pub intervals: IntervalMode,
+    pub key: SortKey,
+    pub order: SortOrder,
+    // Further cuts every interval into pieces of random length up to the max
+    pub random: Option<RandomIntervals>,
+    // Only used for the Sobel pass of edge intervals
+    pub border: BorderMode,
+}
+
+impl PixelSortData {
+    pub fn create() -> PixelSortData {
+        PixelSortData {
+            direction: SortDirection::Rows,
+            intervals: IntervalMode::Threshold {
+                key: SortKey::Luminance,
+                low: 0.25,
+                high: 0.8,
+            },
+            key: SortKey::Luminance,
+            order: SortOrder::Ascending,
+            random: None,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn direction(mut self, direction: SortDirection) -> PixelSortData {
+        self.direction = direction;
+        self
+    }
+
+    pub fn threshold(mut self, key: SortKey, low: f32, high: f32) -> PixelSortData {
+        assert!(
+            low <= high,
+            "Low threshold must not be above the high threshold"
+        );
+        self.intervals = IntervalMode::Threshold { key, low, high };
+        self
+    }
+
+    pub fn edges(mut self, threshold: f32) -> PixelSortData {
+        assert!(threshold >= 0.0, "Edge threshold must not be negative");
+        self.intervals = IntervalMode::Edges { threshold };
+        self
+    }
+
+    pub fn key(mut self, key: SortKey) -> PixelSortData {
+        self.key = key;
+        self
+    }
+
+    pub fn order(mut self, order: SortOrder) -> PixelSortData {
+        self.order = order;
+        self
+    }
+
+    pub fn random_lengths(mut self, max_length: u32, seed: u64) -> PixelSortData {
+        assert!(max_length > 0, "Max interval length must be positive");
+        self.random = Some(RandomIntervals { max_length, seed });
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> PixelSortData {
+        self.border = border;
+        self
+    }
+}
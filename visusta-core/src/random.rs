@@ -0,0 +1,41 @@
+// SplitMix64, small and deterministic for a given seed across platforms
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(self.state)
+    }
+
+    // Uniform in 0 - 1
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, max: u64) -> u64 {
+        self.next_u64() % max.max(1)
+    }
+}
+
+// Stateless value for a coordinate so parallel rows don't share a generator
+pub fn hash_f32(seed: u64, x: u32, y: u32, channel: u32) -> f32 {
+    let key = seed
+        ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (channel as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+
+    (mix(key) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,126 @@
+use crate::border::BorderMode;
+
+// A cutoff that is either hand tuned or picked per image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cutoff {
+    Value(f32),
+    Otsu,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ThresholdFilter {
+    Fixed(u8),
+    Otsu,
+    Adaptive(AdaptiveThreshold),
+    Posterize(PosterizeLevels),
+}
+
+impl ThresholdFilter {
+    pub fn posterize(levels: u8) -> ThresholdFilter {
+        ThresholdFilter::Posterize(PosterizeLevels::create(levels))
+    }
+}
+
+// Number of evenly spaced levels, only built through create so it is always 2 or more
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PosterizeLevels(u8);
+
+impl PosterizeLevels {
+    pub fn create(levels: u8) -> PosterizeLevels {
+        assert!(levels >= 2, "Posterize needs at least two levels");
+        PosterizeLevels(levels)
+    }
+
+    pub fn get(self) -> u8 {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdaptiveMethod {
+    Mean,
+    Gaussian,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AdaptiveThreshold {
+    pub method: AdaptiveMethod,
+    // Odd neighbourhood width in pixels
+    pub block_size: u32,
+    // Subtracted from the local mean before comparing
+    pub offset: f32,
+    pub border: BorderMode,
+}
+
+impl AdaptiveThreshold {
+    pub fn create() -> AdaptiveThreshold {
+        AdaptiveThreshold {
+            method: AdaptiveMethod::Mean,
+            block_size: 15,
+            offset: 5.0,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn method(mut self, method: AdaptiveMethod) -> AdaptiveThreshold {
+        self.method = method;
+        self
+    }
+
+    pub fn block_size(mut self, block_size: u32) -> AdaptiveThreshold {
+        assert!(
+            block_size >= 3 && block_size % 2 == 1,
+            "Block size must be odd and at least 3"
+        );
+        self.block_size = block_size;
+        self
+    }
+
+    pub fn offset(mut self, offset: f32) -> AdaptiveThreshold {
+        self.offset = offset;
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> AdaptiveThreshold {
+        self.border = border;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PosterizeData {
+    pub r: PosterizeLevels,
+    pub g: PosterizeLevels,
+    pub b: PosterizeLevels,
+}
+
+impl PosterizeData {
+    pub fn create(levels: u8) -> PosterizeData {
+        let levels = PosterizeLevels::create(levels);
+        PosterizeData {
+            r: levels,
+            g: levels,
+            b: levels,
+        }
+    }
+
+    pub fn r(mut self, levels: u8) -> PosterizeData {
+        self.r = PosterizeLevels::create(levels);
+        self
+    }
+
+    pub fn g(mut self, levels: u8) -> PosterizeData {
+        self.g = PosterizeLevels::create(levels);
+        self
+    }
+
+    pub fn b(mut self, levels: u8) -> PosterizeData {
+        self.b = PosterizeLevels::create(levels);
+        self
+    }
+}
+
+pub fn posterize_value(val: u8, levels: PosterizeLevels) -> u8 {
+    let steps = (levels.get() - 1) as f32;
+    ((val as f32 / 255.0 * steps).round() / steps * 255.0).round() as u8
+}
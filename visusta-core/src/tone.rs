@@ -0,0 +1,267 @@
+pub type ToneLut = [u8; 256];
+
+#[derive(Debug, Clone)]
+pub struct ToneLuts {
+    pub r: ToneLut,
+    pub g: ToneLut,
+    pub b: ToneLut,
+    pub luma: ToneLut,
+}
+
+#[derive(Debug, Clone)]
+pub enum ToneAdjustment {
+    Levels(Levels),
+    BrightnessContrast(BrightnessContrast),
+    Curve(ToneCurve),
+}
+
+impl ToneAdjustment {
+    pub fn build_lut(&self) -> ToneLut {
+        let mut lut = [0u8; 256];
+
+        for (idx, out) in lut.iter_mut().enumerate() {
+            let val = match self {
+                ToneAdjustment::Levels(levels) => levels.apply(idx as f32),
+                ToneAdjustment::BrightnessContrast(bc) => bc.apply(idx as f32),
+                ToneAdjustment::Curve(curve) => curve.apply(idx as f32),
+            };
+
+            *out = val.round().clamp(0.0, 255.0) as u8;
+        }
+
+        lut
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Levels {
+    pub black: u8,
+    pub white: u8,
+    pub gamma: f32,
+}
+
+impl Levels {
+    pub fn create(black: u8, white: u8) -> Levels {
+        assert!(black < white, "Black point must be below white point");
+
+        Levels {
+            black,
+            white,
+            gamma: 1.0,
+        }
+    }
+
+    pub fn gamma(mut self, gamma: f32) -> Levels {
+        assert!(gamma > 0.0, "Gamma must be positive");
+        self.gamma = gamma;
+        self
+    }
+
+    fn apply(&self, val: f32) -> f32 {
+        let black = self.black as f32;
+        let white = self.white as f32;
+
+        let normal = ((val - black) / (white - black)).clamp(0.0, 1.0);
+
+        normal.powf(1.0 / self.gamma) * 255.0
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BrightnessContrast {
+    pub brightness: f32,
+    pub contrast: f32,
+}
+
+impl BrightnessContrast {
+    pub fn create() -> BrightnessContrast {
+        BrightnessContrast {
+            brightness: 0.0,
+            contrast: 1.0,
+        }
+    }
+
+    // Offset added after contrast, in luminance units (-255 to 255)
+    pub fn brightness(mut self, brightness: f32) -> BrightnessContrast {
+        self.brightness = brightness;
+        self
+    }
+
+    // Scale around mid grey, 1.0 leaves the image unchanged
+    pub fn contrast(mut self, contrast: f32) -> BrightnessContrast {
+        assert!(contrast >= 0.0, "Contrast must not be negative");
+        self.contrast = contrast;
+        self
+    }
+
+    fn apply(&self, val: f32) -> f32 {
+        (val - 127.5) * self.contrast + 127.5 + self.brightness
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ToneCurve {
+    xs: Vec<f32>,
+    ys: Vec<f32>,
+    tangents: Vec<f32>,
+}
+
+impl ToneCurve {
+    pub fn create(points: &[(u8, u8)]) -> ToneCurve {
+        assert!(points.len() >= 2, "Curve needs at least 2 control points");
+
+        let mut points = points.to_vec();
+        points.sort_by_key(|(x, _)| *x);
+
+        assert!(
+            points.windows(2).all(|pair| pair[0].0 != pair[1].0),
+            "Curve control points must have unique x values"
+        );
+
+        let xs: Vec<f32> = points.iter().map(|(x, _)| *x as f32).collect();
+        let ys: Vec<f32> = points.iter().map(|(_, y)| *y as f32).collect();
+
+        let tangents = Self::calculate_tangents(&xs, &ys);
+
+        ToneCurve { xs, ys, tangents }
+    }
+
+    // Fritsch-Carlson tangents, keeps the curve monotone between control points
+    fn calculate_tangents(xs: &[f32], ys: &[f32]) -> Vec<f32> {
+        let count = xs.len();
+
+        let secants: Vec<f32> = (0..count - 1)
+            .map(|idx| (ys[idx + 1] - ys[idx]) / (xs[idx + 1] - xs[idx]))
+            .collect();
+
+        let mut tangents = vec![0f32; count];
+        tangents[0] = secants[0];
+        tangents[count - 1] = secants[count - 2];
+
+        for idx in 1..count - 1 {
+            let (prev, next) = (secants[idx - 1], secants[idx]);
+            tangents[idx] = if prev * next <= 0.0 {
+                0.0
+            } else {
+                (prev + next) / 2.0
+            };
+        }
+
+        for (idx, secant) in secants.iter().enumerate() {
+            if *secant == 0.0 {
+                tangents[idx] = 0.0;
+                tangents[idx + 1] = 0.0;
+                continue;
+            }
+
+            let alpha = tangents[idx] / secant;
+            let beta = tangents[idx + 1] / secant;
+            let len_sq = alpha * alpha + beta * beta;
+
+            if len_sq > 9.0 {
+                let tau = 3.0 / len_sq.sqrt();
+                tangents[idx] = tau * alpha * secant;
+                tangents[idx + 1] = tau * beta * secant;
+            }
+        }
+
+        tangents
+    }
+
+    fn apply(&self, val: f32) -> f32 {
+        let last = self.xs.len() - 1;
+
+        if val <= self.xs[0] {
+            return self.ys[0];
+        }
+        if val >= self.xs[last] {
+            return self.ys[last];
+        }
+
+        let idx = self.xs.partition_point(|x| *x <= val) - 1;
+
+        let h = self.xs[idx + 1] - self.xs[idx];
+        let t = (val - self.xs[idx]) / h;
+        let t2 = t * t;
+        let t3 = t2 * t;
+
+        let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+        let h10 = t3 - 2.0 * t2 + t;
+        let h01 = -2.0 * t3 + 3.0 * t2;
+        let h11 = t3 - t2;
+
+        h00 * self.ys[idx]
+            + h10 * h * self.tangents[idx]
+            + h01 * self.ys[idx + 1]
+            + h11 * h * self.tangents[idx + 1]
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ToneFilter {
+    pub all: Option<ToneAdjustment>,
+    pub r: Option<ToneAdjustment>,
+    pub g: Option<ToneAdjustment>,
+    pub b: Option<ToneAdjustment>,
+}
+
+impl ToneFilter {
+    pub fn create(adjustment: ToneAdjustment) -> ToneFilter {
+        ToneFilter {
+            all: Some(adjustment),
+            r: None,
+            g: None,
+            b: None,
+        }
+    }
+
+    pub fn per_channel() -> ToneFilter {
+        ToneFilter {
+            all: None,
+            r: None,
+            g: None,
+            b: None,
+        }
+    }
+
+    pub fn r(mut self, adjustment: ToneAdjustment) -> ToneFilter {
+        self.r = Some(adjustment);
+        self
+    }
+
+    pub fn g(mut self, adjustment: ToneAdjustment) -> ToneFilter {
+        self.g = Some(adjustment);
+        self
+    }
+
+    pub fn b(mut self, adjustment: ToneAdjustment) -> ToneFilter {
+        self.b = Some(adjustment);
+        self
+    }
+
+    // Channel adjustments are applied on top of the shared one,
+    //  luminance images only use the shared adjustment
+    pub fn build_luts(&self) -> ToneLuts {
+        let identity: ToneLut = std::array::from_fn(|idx| idx as u8);
+
+        let luma = self
+            .all
+            .as_ref()
+            .map_or(identity, ToneAdjustment::build_lut);
+
+        let channel = |adjustment: &Option<ToneAdjustment>| match adjustment {
+            Some(adjustment) => {
+                let lut = adjustment.build_lut();
+                std::array::from_fn(|idx| lut[luma[idx] as usize])
+            }
+            None => luma,
+        };
+
+        ToneLuts {
+            r: channel(&self.r),
+            g: channel(&self.g),
+            b: channel(&self.b),
+            luma,
+        }
+    }
+}
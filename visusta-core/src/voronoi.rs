@@ -0,0 +1,147 @@
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy)]
+pub struct StainedGlassData {
+    pub cell_count: u32,
+    pub seed: u64,
+    // Width in pixels of the lead between cells, 0 leaves the cells touching
+    pub lead_width: f32,
+    pub lead_colour: [u8; 4],
+}
+
+impl StainedGlassData {
+    pub fn create(seed: u64) -> StainedGlassData {
+        StainedGlassData {
+            cell_count: 500,
+            seed,
+            lead_width: 2.0,
+            lead_colour: [20, 20, 20, 255],
+        }
+    }
+
+    pub fn cell_count(mut self, cell_count: u32) -> StainedGlassData {
+        assert!(cell_count > 0, "Cell count must be positive");
+        self.cell_count = cell_count;
+        self
+    }
+
+    pub fn lead_width(mut self, lead_width: f32) -> StainedGlassData {
+        assert!(lead_width >= 0.0, "Lead width must not be negative");
+        self.lead_width = lead_width;
+        self
+    }
+
+    pub fn lead_colour(mut self, lead_colour: [u8; 4]) -> StainedGlassData {
+        self.lead_colour = lead_colour;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StippleData {
+    pub dot_count: u32,
+    pub seed: u64,
+    // Lloyd relaxation passes, each moves every dot to its cell's darkness weighted centroid
+    pub iterations: u32,
+    pub dot_radius: f32,
+    pub dot_colour: [u8; 4],
+    pub background: [u8; 4],
+}
+
+impl StippleData {
+    pub fn create(seed: u64) -> StippleData {
+        StippleData {
+            dot_count: 2000,
+            seed,
+            iterations: 30,
+            dot_radius: 1.2,
+            dot_colour: [0, 0, 0, 255],
+            background: [255, 255, 255, 255],
+        }
+    }
+
+    pub fn dot_count(mut self, dot_count: u32) -> StippleData {
+        assert!(dot_count > 0, "Dot count must be positive");
+        self.dot_count = dot_count;
+        self
+    }
+
+    pub fn iterations(mut self, iterations: u32) -> StippleData {
+        self.iterations = iterations;
+        self
+    }
+
+    pub fn dot_radius(mut self, dot_radius: f32) -> StippleData {
+        assert!(dot_radius > 0.0, "Dot radius must be positive");
+        self.dot_radius = dot_radius;
+        self
+    }
+
+    pub fn dot_colour(mut self, dot_colour: [u8; 4]) -> StippleData {
+        self.dot_colour = dot_colour;
+        self
+    }
+
+    pub fn background(mut self, background: [u8; 4]) -> StippleData {
+        self.background = background;
+        self
+    }
+}
+
+pub struct StippleSet {
+    pub width: u32,
+    pub height: u32,
+    pub points: Vec<(f32, f32)>,
+    pub radius: f32,
+    pub dot_colour: [u8; 4],
+    pub background: [u8; 4],
+}
+
+impl StippleSet {
+    pub fn to_svg(&self) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = self.width,
+            h = self.height
+        );
+
+        if self.background[3] > 0 {
+            let _ = writeln!(
+                svg,
+                "  <rect width=\"100%\" height=\"100%\" fill=\"{}\"{}/>",
+                hex(self.background),
+                opacity(self.background)
+            );
+        }
+
+        let _ = writeln!(
+            svg,
+            "  <g fill=\"{}\"{}>",
+            hex(self.dot_colour),
+            opacity(self.dot_colour)
+        );
+
+        for (x, y) in self.points.iter() {
+            let _ = writeln!(
+                svg,
+                "    <circle cx=\"{x:.2}\" cy=\"{y:.2}\" r=\"{:.2}\"/>",
+                self.radius
+            );
+        }
+
+        svg.push_str("  </g>\n</svg>\n");
+        svg
+    }
+}
+
+fn hex(colour: [u8; 4]) -> String {
+    format!("#{:02x}{:02x}{:02x}", colour[0], colour[1], colour[2])
+}
+
+fn opacity(colour: [u8; 4]) -> String {
+    if colour[3] < 255 {
+        format!(" fill-opacity=\"{:.3}\"", colour[3] as f32 / 255.0)
+    } else {
+        String::new()
+    }
+}
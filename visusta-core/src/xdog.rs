@@ -0,0 +1,103 @@
+// Extended DoG from Winnemöller et al.
+//  D = (1 + p) * G(sigma) - p * G(k * sigma), on luminance in 0 - 1
+//  T = 1 where D >= epsilon, otherwise 1 + tanh(phi * (D - epsilon))
+use crate::border::BorderMode;
+
+#[derive(Debug, Clone, Copy)]
+pub struct XDogData {
+    pub sigma: f32,
+    pub k: f32,
+    pub p: f32,
+    pub epsilon: f32,
+    pub phi: f32,
+    pub mode: XDogMode,
+    pub border: BorderMode,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum XDogMode {
+    Isotropic,
+    // DoG taken across the edge tangent flow then smoothed along it
+    FlowBased(FlowData),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FlowData {
+    // Smoothing of the structure tensor the tangent flow is read from
+    pub tensor_sigma: f32,
+    // Smoothing along the flow lines, longer gives more coherent strokes
+    pub line_sigma: f32,
+}
+
+impl XDogData {
+    pub fn create() -> XDogData {
+        XDogData {
+            sigma: 0.8,
+            k: 1.6,
+            p: 20.0,
+            epsilon: 0.2,
+            phi: 10.0,
+            mode: XDogMode::Isotropic,
+            border: BorderMode::Clamp,
+        }
+    }
+
+    pub fn sigma(mut self, sigma: f32) -> XDogData {
+        assert!(sigma > 0.0, "Sigma must be positive");
+        self.sigma = sigma;
+        self
+    }
+
+    pub fn k(mut self, k: f32) -> XDogData {
+        assert!(k > 1.0, "K must be greater than 1");
+        self.k = k;
+        self
+    }
+
+    pub fn p(mut self, p: f32) -> XDogData {
+        self.p = p;
+        self
+    }
+
+    pub fn epsilon(mut self, epsilon: f32) -> XDogData {
+        self.epsilon = epsilon;
+        self
+    }
+
+    pub fn phi(mut self, phi: f32) -> XDogData {
+        assert!(phi > 0.0, "Phi must be positive");
+        self.phi = phi;
+        self
+    }
+
+    pub fn flow_based(mut self, flow: FlowData) -> XDogData {
+        self.mode = XDogMode::FlowBased(flow);
+        self
+    }
+
+    pub fn border(mut self, border: BorderMode) -> XDogData {
+        self.border = border;
+        self
+    }
+}
+
+impl FlowData {
+    pub fn create() -> FlowData {
+        FlowData {
+            tensor_sigma: 2.0,
+            line_sigma: 3.0,
+        }
+    }
+
+    pub fn tensor_sigma(mut self, tensor_sigma: f32) -> FlowData {
+        assert!(tensor_sigma > 0.0, "Tensor sigma must be positive");
+        self.tensor_sigma = tensor_sigma;
+        self
+    }
+
+    pub fn line_sigma(mut self, line_sigma: f32) -> FlowData {
+        assert!(line_sigma > 0.0, "Line sigma must be positive");
+        self.line_sigma = line_sigma;
+        self
+    }
+}
@@ -0,0 +1,116 @@
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::auxiliary::{BlendData, SizePolicy};
+
+pub fn fit_rgba(img: &RgbaImage, width: u32, height: u32, fit: SizePolicy) -> RgbaImage {
+    if img.dimensions() == (width, height) {
+        return img.clone();
+    }
+
+    let (src_w, src_h) = img.dimensions();
+    let mut buf = vec![0u8; width as usize * height as usize * 4];
+
+    buf.par_chunks_mut(width as usize * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let (x, y) = (x as u32, y as u32);
+
+                let val = match fit {
+                    SizePolicy::Stretch => stretch_sample(img, width, height, x, y),
+                    SizePolicy::Tile => img.get_pixel(x % src_w, y % src_h).0,
+                    SizePolicy::Centre => {
+                        // Offsets go negative when the image is larger than the target
+                        let sx = x as i64 + (src_w as i64 - width as i64) / 2;
+                        let sy = y as i64 + (src_h as i64 - height as i64) / 2;
+
+                        if (0..src_w as i64).contains(&sx) && (0..src_h as i64).contains(&sy) {
+                            img.get_pixel(sx as u32, sy as u32).0
+                        } else {
+                            [0, 0, 0, 0]
+                        }
+                    }
+                };
+
+                pixel.copy_from_slice(&val);
+            }
+        });
+
+    ImageBuffer::from_raw(width, height, buf).expect("Fitted buffer should be sized correctly")
+}
+
+// Bilinear between pixel centres, premultiplied so transparent pixels don't bleed black
+fn stretch_sample(img: &RgbaImage, width: u32, height: u32, x: u32, y: u32) -> [u8; 4] {
+    let (src_w, src_h) = img.dimensions();
+
+    let fx = ((x as f32 + 0.5) * src_w as f32 / width as f32 - 0.5).clamp(0.0, (src_w - 1) as f32);
+    let fy = ((y as f32 + 0.5) * src_h as f32 / height as f32 - 0.5).clamp(0.0, (src_h - 1) as f32);
+
+    let (x0, y0) = (fx.floor() as u32, fy.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(src_w - 1), (y0 + 1).min(src_h - 1));
+    let (wx, wy) = (fx - x0 as f32, fy - y0 as f32);
+
+    let mut acc = [0f32; 4];
+
+    for (px, py, weight) in [
+        (x0, y0, (1.0 - wx) * (1.0 - wy)),
+        (x1, y0, wx * (1.0 - wy)),
+        (x0, y1, (1.0 - wx) * wy),
+        (x1, y1, wx * wy),
+    ] {
+        let [r, g, b, a] = img.get_pixel(px, py).0;
+        let alpha = a as f32 * weight;
+
+        acc[0] += r as f32 * alpha;
+        acc[1] += g as f32 * alpha;
+        acc[2] += b as f32 * alpha;
+        acc[3] += alpha;
+    }
+
+    if acc[3] <= 0.0 {
+        return [0, 0, 0, 0];
+    }
+
+    [
+        (acc[0] / acc[3]).round() as u8,
+        (acc[1] / acc[3]).round() as u8,
+        (acc[2] / acc[3]).round() as u8,
+        acc[3].round() as u8,
+    ]
+}
+
+// Where the base is transparent the top shows unblended, as in W3C compositing
+pub fn blend_rgba(img: &RgbaImage, top: &RgbaImage, data: BlendData) -> RgbaImage {
+    assert!(
+        img.dimensions() == top.dimensions(),
+        "Blended images must have same dimensions"
+    );
+
+    let mut out = img.clone();
+
+    out.par_chunks_mut(4)
+        .zip(top.par_chunks(4))
+        .for_each(|(pixel, fg)| {
+            let alpha_fg = fg[3] as f32 / 255.0 * data.opacity;
+            if alpha_fg == 0.0 {
+                return;
+            }
+
+            let alpha_bg = pixel[3] as f32 / 255.0;
+            let alpha_out = alpha_fg + alpha_bg * (1.0 - alpha_fg);
+
+            for channel in 0..3 {
+                let base = pixel[channel] as f32 / 255.0;
+                let top = fg[channel] as f32 / 255.0;
+
+                let mixed = alpha_bg * data.mode.blend(base, top) + (1.0 - alpha_bg) * top;
+                let val = (alpha_fg * mixed + alpha_bg * (1.0 - alpha_fg) * base) / alpha_out;
+
+                pixel[channel] = (val * 255.0).round().clamp(0.0, 255.0) as u8;
+            }
+
+            pixel[3] = (alpha_out * 255.0).round() as u8;
+        });
+
+    out
+}
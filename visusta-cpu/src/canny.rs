@@ -0,0 +1,168 @@
+use std::f32::consts::PI;
+
+use image::ImageBuffer;
+use libm::atan2f;
+use rayon::prelude::*;
+use visusta_core::{
+    LumaAImage,
+    border::BorderMode,
+    canny::{CannyData, CannyThresholds},
+    gaussians::gaussian_kernel_1d,
+    gradient::GradientOperator,
+};
+
+use crate::{
+    convolve::{Plane, gradients, separable_blur},
+    histogram::otsu_threshold,
+};
+
+// A unit step of 255 gives a Sobel response of 4 * 255
+const SOBEL_GAIN: f32 = 4.0;
+
+pub fn canny_on_luma(img: &LumaAImage, data: CannyData) -> LumaAImage {
+    let blurred = separable_blur(
+        &Plane::from_luma(img),
+        &gaussian_kernel_1d(data.sigma),
+        data.border,
+    );
+    let (gx, gy) = gradients(&blurred, GradientOperator::Sobel3, data.border);
+
+    let edges = edges_from_gradients(&gx, &gy, data.thresholds, data.border);
+
+    let buf = edges
+        .iter()
+        .flat_map(|edge| if *edge { [255u8, 255u8] } else { [0u8, 0u8] })
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Canny buffer should be sized correctly")
+}
+
+// Non-maximum suppression and hysteresis on gradients in Sobel 3x3 units
+pub fn edges_from_gradients(
+    gx: &Plane,
+    gy: &Plane,
+    thresholds: CannyThresholds,
+    border: BorderMode,
+) -> Vec<bool> {
+    let width = gx.width;
+    let height = gx.height;
+
+    let magnitude = Plane {
+        width,
+        height,
+        data: gx
+            .data
+            .iter()
+            .zip(gy.data.iter())
+            .map(|(gx, gy)| ((gx * gx + gy * gy).sqrt() / SOBEL_GAIN).min(255.0))
+            .collect(),
+    };
+
+    let suppressed = non_maximum_suppression(&magnitude, gx, gy, border);
+
+    let (low, high) = match thresholds {
+        CannyThresholds::Manual { low, high } => (low, high),
+        CannyThresholds::Otsu => {
+            let mut hist = [0u32; 256];
+            for val in suppressed.iter().filter(|val| **val > 0.0) {
+                hist[*val as usize] += 1;
+            }
+
+            let high = otsu_threshold(&hist).max(1) as f32;
+            (high / 2.0, high)
+        }
+    };
+
+    hysteresis(&suppressed, width, height, low, high)
+}
+
+fn non_maximum_suppression(
+    magnitude: &Plane,
+    gx: &Plane,
+    gy: &Plane,
+    border: BorderMode,
+) -> Vec<f32> {
+    let width = magnitude.width;
+
+    let mut suppressed = vec![0f32; width * magnitude.height];
+
+    suppressed
+        .par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, out) in row.iter_mut().enumerate() {
+                let idx = y * width + x;
+                let mag = magnitude.data[idx];
+
+                if mag == 0.0 {
+                    continue;
+                }
+
+                let mut dir = atan2f(gy.data[idx], gx.data[idx]);
+                if dir < 0.0 {
+                    dir += PI;
+                }
+
+                let eighth = PI / 8.0;
+
+                // Neighbours along the gradient, y grows downwards
+                let (dx, dy) = if !(eighth..eighth * 7.0).contains(&dir) {
+                    (1, 0)
+                } else if dir < eighth * 3.0 {
+                    (1, 1)
+                } else if dir < eighth * 5.0 {
+                    (0, 1)
+                } else {
+                    (-1, 1)
+                };
+
+                let (x, y) = (x as isize, y as isize);
+                let ahead = magnitude.get_border(x + dx, y + dy, border);
+                let behind = magnitude.get_border(x - dx, y - dy, border);
+
+                if mag >= ahead && mag > behind {
+                    *out = mag;
+                }
+            }
+        });
+
+    suppressed
+}
+
+fn hysteresis(suppressed: &[f32], width: usize, height: usize, low: f32, high: f32) -> Vec<bool> {
+    let mut edges = vec![false; width * height];
+
+    let mut stack: Vec<usize> = suppressed
+        .iter()
+        .enumerate()
+        .filter(|(_, val)| **val >= high)
+        .map(|(idx, _)| idx)
+        .collect();
+
+    for idx in stack.iter() {
+        edges[*idx] = true;
+    }
+
+    while let Some(idx) = stack.pop() {
+        let x = (idx % width) as isize;
+        let y = (idx / width) as isize;
+
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                    continue;
+                }
+
+                let neighbour = ny as usize * width + nx as usize;
+                if !edges[neighbour] && suppressed[neighbour] >= low {
+                    edges[neighbour] = true;
+                    stack.push(neighbour);
+                }
+            }
+        }
+    }
+
+    edges
+}
@@ -0,0 +1,250 @@
+use image::RgbaImage;
+use rayon::prelude::*;
+use visusta_core::{
+    LumaAImage, border::BorderMode, gaussians::GaussianKernelData, gradient::GradientOperator,
+};
+
+pub struct Plane {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<f32>,
+}
+
+impl Plane {
+    pub fn from_luma(img: &LumaAImage) -> Plane {
+        Plane {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            data: img.pixels().map(|pixel| pixel.0[0] as f32).collect(),
+        }
+    }
+
+    pub fn from_rgba_luminance(img: &RgbaImage) -> Plane {
+        Plane {
+            width: img.width() as usize,
+            height: img.height() as usize,
+            data: img
+                .pixels()
+                .map(|pixel| {
+                    0.299 * pixel.0[0] as f32
+                        + 0.587 * pixel.0[1] as f32
+                        + 0.114 * pixel.0[2] as f32
+                })
+                .collect(),
+        }
+    }
+
+    // Out of bounds coordinates are clamped to the nearest edge pixel
+    pub fn get(&self, x: isize, y: isize) -> f32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.data[y * self.width + x]
+    }
+
+    pub fn get_border(&self, x: isize, y: isize, border: BorderMode) -> f32 {
+        match (border.index(x, self.width), border.index(y, self.height)) {
+            (Some(x), Some(y)) => self.data[y * self.width + x],
+            _ => border.constant(),
+        }
+    }
+
+    pub fn sample(&self, x: f32, y: f32) -> f32 {
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let (wx, wy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as isize, y0 as isize);
+
+        let top = self.get(x0, y0) * (1.0 - wx) + self.get(x0 + 1, y0) * wx;
+        let bottom = self.get(x0, y0 + 1) * (1.0 - wx) + self.get(x0 + 1, y0 + 1) * wx;
+
+        top * (1.0 - wy) + bottom * wy
+    }
+}
+
+// Area average over 2x2 blocks, odd edges average what is left
+pub fn downsample_half(plane: &Plane) -> Plane {
+    let width = plane.width.div_ceil(2);
+    let height = plane.height.div_ceil(2);
+
+    let mut data = vec![0f32; width * height];
+
+    data.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        for (x, out) in row.iter_mut().enumerate() {
+            let mut sum = 0f32;
+            let mut count = 0f32;
+
+            for sy in (y * 2)..(y * 2 + 2).min(plane.height) {
+                for sx in (x * 2)..(x * 2 + 2).min(plane.width) {
+                    sum += plane.data[sy * plane.width + sx];
+                    count += 1.0;
+                }
+            }
+
+            *out = sum / count;
+        }
+    });
+
+    Plane {
+        width,
+        height,
+        data,
+    }
+}
+
+// Bilinear resample so pixel centres line up between the two sizes
+pub fn resample(plane: &Plane, width: usize, height: usize) -> Plane {
+    let scale_x = plane.width as f32 / width as f32;
+    let scale_y = plane.height as f32 / height as f32;
+
+    let mut data = vec![0f32; width * height];
+
+    data.par_chunks_mut(width).enumerate().for_each(|(y, row)| {
+        let sy = (y as f32 + 0.5) * scale_y - 0.5;
+        for (x, out) in row.iter_mut().enumerate() {
+            *out = plane.sample((x as f32 + 0.5) * scale_x - 0.5, sy);
+        }
+    });
+
+    Plane {
+        width,
+        height,
+        data,
+    }
+}
+
+pub fn separable_blur(plane: &Plane, kernel: &[f32], border: BorderMode) -> Plane {
+    let width = plane.width;
+    let height = plane.height;
+    let radius = (kernel.len() / 2) as isize;
+
+    let mut horizontal = vec![0f32; width * height];
+
+    horizontal
+        .par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, out) in row.iter_mut().enumerate() {
+                *out = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(k, weight)| {
+                        weight
+                            * plane.get_border(x as isize + k as isize - radius, y as isize, border)
+                    })
+                    .sum();
+            }
+        });
+
+    let horizontal = Plane {
+        width,
+        height,
+        data: horizontal,
+    };
+
+    let mut vertical = vec![0f32; width * height];
+
+    vertical
+        .par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, out) in row.iter_mut().enumerate() {
+                *out = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(k, weight)| {
+                        weight
+                            * horizontal.get_border(
+                                x as isize,
+                                y as isize + k as isize - radius,
+                                border,
+                            )
+                    })
+                    .sum();
+            }
+        });
+
+    Plane {
+        width,
+        height,
+        data: vertical,
+    }
+}
+
+// Gradients for any operator, scaled to Sobel 3x3 units so thresholds carry over
+pub fn gradients(plane: &Plane, operator: GradientOperator, border: BorderMode) -> (Plane, Plane) {
+    let width = plane.width;
+    let height = plane.height;
+
+    let (smooth, derivative, start) = operator.taps();
+    let scale = GradientOperator::Sobel3.slope_gain() / operator.slope_gain();
+
+    // (dx, dy, x weight, y weight) for every tap in the window
+    let taps: Vec<(isize, isize, f32, f32)> = (0..smooth.len())
+        .flat_map(|j| {
+            (0..smooth.len()).map(move |i| {
+                (
+                    start + i as isize,
+                    start + j as isize,
+                    derivative[i] * smooth[j],
+                    smooth[i] * derivative[j],
+                )
+            })
+        })
+        .filter(|(_, _, wx, wy)| *wx != 0.0 || *wy != 0.0)
+        .collect();
+
+    let mut gx_buff = vec![0f32; width * height];
+    let mut gy_buff = vec![0f32; width * height];
+
+    gx_buff
+        .par_chunks_mut(width)
+        .zip(gy_buff.par_chunks_mut(width))
+        .enumerate()
+        .for_each(|(y, (gx_row, gy_row))| {
+            let y = y as isize;
+
+            for x in 0..width {
+                let xi = x as isize;
+
+                let (mut gx, mut gy) = (0.0, 0.0);
+                for (dx, dy, wx, wy) in taps.iter() {
+                    let val = plane.get_border(xi + dx, y + dy, border);
+                    gx += wx * val;
+                    gy += wy * val;
+                }
+
+                gx_row[x] = gx * scale;
+                gy_row[x] = gy * scale;
+            }
+        });
+
+    (
+        Plane {
+            width,
+            height,
+            data: gx_buff,
+        },
+        Plane {
+            width,
+            height,
+            data: gy_buff,
+        },
+    )
+}
+
+// Continuous DoG response, blur_a - scalar * blur_b
+pub fn difference_of_gaussians(plane: &Plane, kernel_data: &GaussianKernelData) -> Plane {
+    let blur_a = separable_blur(plane, &kernel_data.kernel_a, kernel_data.border);
+    let blur_b = separable_blur(plane, &kernel_data.kernel_b, kernel_data.border);
+
+    Plane {
+        width: plane.width,
+        height: plane.height,
+        data: blur_a
+            .data
+            .iter()
+            .zip(blur_b.data.iter())
+            .map(|(a, b)| a - kernel_data.scalar * b)
+            .collect(),
+    }
+}
@@ -0,0 +1,118 @@
+// Bowyer-Watson, fine for the few thousand points the low poly step uses
+pub fn triangulate(points: &[(f32, f32)]) -> Vec<[usize; 3]> {
+    if points.len() < 3 {
+        return Vec::new();
+    }
+
+    let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+    let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+
+    let mut vertices: Vec<(f64, f64)> = points
+        .iter()
+        .map(|(x, y)| {
+            let (x, y) = (*x as f64, *y as f64);
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+            (x, y)
+        })
+        .collect();
+
+    // Super triangle comfortably containing every point
+    let span = (max_x - min_x).max(max_y - min_y).max(1.0) * 20.0;
+    let (mid_x, mid_y) = ((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+
+    let first_super = vertices.len();
+    vertices.push((mid_x - span, mid_y - span));
+    vertices.push((mid_x, mid_y + span));
+    vertices.push((mid_x + span, mid_y - span));
+
+    let mut triangles = vec![Triangle::new(
+        &vertices,
+        [first_super, first_super + 1, first_super + 2],
+    )];
+
+    for point_idx in 0..points.len() {
+        let point = vertices[point_idx];
+
+        let mut edges: Vec<[usize; 2]> = Vec::new();
+
+        triangles.retain(|triangle| {
+            if !triangle.circumcircle_contains(point) {
+                return true;
+            }
+
+            let [a, b, c] = triangle.vertices;
+            edges.extend([[a, b], [b, c], [c, a]]);
+            false
+        });
+
+        // Edges shared by two removed triangles are inside the hole
+        let boundary: Vec<[usize; 2]> = edges
+            .iter()
+            .filter(|edge| {
+                edges
+                    .iter()
+                    .filter(|other| {
+                        (other[0] == edge[0] && other[1] == edge[1])
+                            || (other[0] == edge[1] && other[1] == edge[0])
+                    })
+                    .count()
+                    == 1
+            })
+            .copied()
+            .collect();
+
+        for [a, b] in boundary {
+            triangles.push(Triangle::new(&vertices, [a, b, point_idx]));
+        }
+    }
+
+    triangles
+        .into_iter()
+        .filter(|triangle| triangle.vertices.iter().all(|idx| *idx < first_super))
+        .filter(|triangle| triangle.radius_sq >= 0.0)
+        .map(|triangle| triangle.vertices)
+        .collect()
+}
+
+struct Triangle {
+    vertices: [usize; 3],
+    centre: (f64, f64),
+    radius_sq: f64,
+}
+
+impl Triangle {
+    fn new(vertices: &[(f64, f64)], indices: [usize; 3]) -> Triangle {
+        let [a, b, c] = indices.map(|idx| vertices[idx]);
+
+        let d = 2.0 * (a.0 * (b.1 - c.1) + b.0 * (c.1 - a.1) + c.0 * (a.1 - b.1));
+
+        let (centre, radius_sq) = if d.abs() < f64::EPSILON {
+            // Collinear, nothing should ever fall inside it
+            ((0.0, 0.0), -1.0)
+        } else {
+            let a_sq = a.0 * a.0 + a.1 * a.1;
+            let b_sq = b.0 * b.0 + b.1 * b.1;
+            let c_sq = c.0 * c.0 + c.1 * c.1;
+
+            let ux = (a_sq * (b.1 - c.1) + b_sq * (c.1 - a.1) + c_sq * (a.1 - b.1)) / d;
+            let uy = (a_sq * (c.0 - b.0) + b_sq * (a.0 - c.0) + c_sq * (b.0 - a.0)) / d;
+
+            ((ux, uy), (a.0 - ux).powi(2) + (a.1 - uy).powi(2))
+        };
+
+        Triangle {
+            vertices: indices,
+            centre,
+            radius_sq,
+        }
+    }
+
+    fn circumcircle_contains(&self, point: (f64, f64)) -> bool {
+        let dx = point.0 - self.centre.0;
+        let dy = point.1 - self.centre.1;
+        dx * dx + dy * dy < self.radius_sq
+    }
+}
@@ -0,0 +1,189 @@
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::{
+    border::BorderMode,
+    effects::{BloomComposite, BloomData, GrainData, VignetteData},
+    gaussians::gaussian_kernel_1d,
+    random::hash_f32,
+};
+
+use crate::convolve::{Plane, downsample_half, resample, separable_blur};
+
+pub fn grain_on_rgba(img: &RgbaImage, data: GrainData) -> RgbaImage {
+    let width = img.width() as usize;
+
+    let mut buf = img.as_raw().clone();
+
+    // Sum of 4 uniforms, close enough to a unit Gaussian for grain
+    let noise = |x: u32, y: u32, channel: u32| {
+        let sum: f32 = (0..4)
+            .map(|octave| hash_f32(data.seed, x, y, channel * 4 + octave))
+            .sum();
+        (sum - 2.0) * 3f32.sqrt()
+    };
+
+    buf.par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let (x, y) = (x as u32, y as u32);
+
+                let luminance =
+                    (0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32)
+                        / 255.0;
+
+                let midtone = 4.0 * luminance * (1.0 - luminance);
+                let response = 1.0 - data.luminance_response * (1.0 - midtone);
+                let amplitude = data.strength * response * 255.0;
+
+                let mono = noise(x, y, 0);
+
+                for (channel, val) in pixel.iter_mut().take(3).enumerate() {
+                    let grain = if data.chroma {
+                        noise(x, y, channel as u32 + 1)
+                    } else {
+                        mono
+                    };
+
+                    *val = (*val as f32 + grain * amplitude).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Grain buffer should be sized correctly")
+}
+
+pub fn vignette_on_rgba(img: &RgbaImage, data: VignetteData) -> RgbaImage {
+    let width = img.width() as usize;
+
+    let half_w = img.width() as f32 / 2.0;
+    let half_h = img.height() as f32 / 2.0;
+
+    let mut buf = img.as_raw().clone();
+
+    buf.par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let ny = (y as f32 + 0.5 - half_h) / half_h;
+
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                // Normalised per axis so the falloff follows the image's aspect as an ellipse
+                let nx = (x as f32 + 0.5 - half_w) / half_w;
+                let dist = (nx * nx + ny * ny).sqrt();
+
+                let amount =
+                    smoothstep(data.radius, data.radius + data.softness, dist) * data.strength;
+
+                for (val, target) in pixel.iter_mut().zip(data.colour) {
+                    let current = *val as f32;
+                    *val = (current + (target as f32 - current) * amount)
+                        .round()
+                        .clamp(0.0, 255.0) as u8;
+                }
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Vignette buffer should be sized correctly")
+}
+
+fn smoothstep(edge_a: f32, edge_b: f32, val: f32) -> f32 {
+    if edge_b <= edge_a {
+        return if val < edge_a { 0.0 } else { 1.0 };
+    }
+
+    let t = ((val - edge_a) / (edge_b - edge_a)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+pub fn bloom_on_rgba(img: &RgbaImage, data: BloomData) -> RgbaImage {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    // Bright pass keeps only what is above the threshold, scaled back up to full range
+    let bright: Vec<Plane> = (0..3)
+        .map(|channel| Plane {
+            width,
+            height,
+            data: img
+                .pixels()
+                .map(|pixel| {
+                    let luminance = 0.299 * pixel.0[0] as f32
+                        + 0.587 * pixel.0[1] as f32
+                        + 0.114 * pixel.0[2] as f32;
+
+                    let contribution =
+                        ((luminance - data.threshold) / (255.0 - data.threshold)).max(0.0);
+
+                    pixel.0[channel] as f32 * contribution * pixel.0[3] as f32 / 255.0
+                })
+                .collect(),
+        })
+        .collect();
+
+    let kernel = gaussian_kernel_1d(data.sigma);
+
+    // A constant grey border goes through the same bright pass as the pixels
+    let border = match data.border {
+        BorderMode::Constant(val) => {
+            BorderMode::Constant(val * ((val - data.threshold) / (255.0 - data.threshold)).max(0.0))
+        }
+        border => border,
+    };
+
+    let glow: Vec<Plane> = bright
+        .par_iter()
+        .map(|plane| {
+            let mut acc = vec![0f32; width * height];
+            let mut level = separable_blur(plane, &kernel, border);
+
+            for depth in 0..data.levels {
+                let upsampled = if depth == 0 {
+                    &level
+                } else {
+                    &resample(&level, width, height)
+                };
+
+                acc.iter_mut()
+                    .zip(upsampled.data.iter())
+                    .for_each(|(acc, val)| *acc += val / data.levels as f32);
+
+                if depth + 1 < data.levels {
+                    level = separable_blur(&downsample_half(&level), &kernel, border);
+                }
+            }
+
+            Plane {
+                width,
+                height,
+                data: acc,
+            }
+        })
+        .collect();
+
+    let mut buf = img.as_raw().clone();
+
+    buf.par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let idx = y * width + x;
+
+                for channel in 0..3 {
+                    let base = pixel[channel] as f32 / 255.0;
+                    let light = glow[channel].data[idx] / 255.0 * data.intensity;
+
+                    let out = match data.composite {
+                        BloomComposite::Additive => base + light,
+                        BloomComposite::Screen => 1.0 - (1.0 - base) * (1.0 - light.min(1.0)),
+                    };
+
+                    pixel[channel] = (out * 255.0).round().clamp(0.0, 255.0) as u8;
+                }
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Bloom buffer should be sized correctly")
+}
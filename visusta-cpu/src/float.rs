@@ -0,0 +1,255 @@
+use image::{ImageBuffer, Rgba32FImage, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::{
+    LumaA32FImage, LumaAImage, LuminanceFilter,
+    border::BorderMode,
+    float::{FloatRange, float_to_u8},
+    gaussians::{GaussianKernelData, gaussian_kernel_1d},
+    gradient::{GradientData, GradientField},
+};
+
+use crate::convolve::{Plane, difference_of_gaussians, gradients, separable_blur};
+
+pub fn rgba_to_float(img: &RgbaImage) -> Rgba32FImage {
+    let buf = img.as_raw().iter().map(|val| *val as f32 / 255.0).collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Float buffer should be sized correctly")
+}
+
+pub fn luma_to_float(img: &LumaAImage) -> LumaA32FImage {
+    let buf = img.as_raw().iter().map(|val| *val as f32 / 255.0).collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Float buffer should be sized correctly")
+}
+
+pub fn rgba_from_float(img: &Rgba32FImage, range: FloatRange) -> RgbaImage {
+    let bounds = range.bounds(
+        img.pixels()
+            .flat_map(|pixel| [pixel.0[0], pixel.0[1], pixel.0[2]]),
+    );
+
+    let buf = img
+        .pixels()
+        .flat_map(|pixel| {
+            [
+                float_to_u8(pixel.0[0], bounds),
+                float_to_u8(pixel.0[1], bounds),
+                float_to_u8(pixel.0[2], bounds),
+                float_to_u8(pixel.0[3], (0.0, 1.0)),
+            ]
+        })
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Rgba buffer should be sized correctly")
+}
+
+pub fn luma_from_float(img: &LumaA32FImage, range: FloatRange) -> LumaAImage {
+    let bounds = range.bounds(img.pixels().map(|pixel| pixel.0[0]));
+
+    let buf = img
+        .pixels()
+        .flat_map(|pixel| {
+            [
+                float_to_u8(pixel.0[0], bounds),
+                float_to_u8(pixel.0[1], (0.0, 1.0)),
+            ]
+        })
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Luminance buffer should be sized correctly")
+}
+
+// Same weights and filter rules as the u8 path, without the truncation or the 255 ceiling
+pub fn rgba_to_luma_f32(img: &Rgba32FImage, filter: LuminanceFilter) -> LumaA32FImage {
+    let min = filter.min as f32 / 255.0;
+
+    let buf = img
+        .pixels()
+        .flat_map(|pixel| {
+            let [r, g, b, a] = pixel.0;
+
+            if a <= 0.0 {
+                return [0.0, 0.0];
+            }
+
+            let luminance = 0.299 * r + 0.587 * g + 0.114 * b;
+
+            let filtered = if filter.multiplier == 0.0 {
+                luminance
+            } else {
+                luminance * filter.multiplier
+            };
+
+            if filtered < min {
+                [0.0, 0.0]
+            } else {
+                [filtered, a]
+            }
+        })
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Luminance buffer should be sized correctly")
+}
+
+fn luma_plane(img: &LumaA32FImage) -> Plane {
+    Plane {
+        width: img.width() as usize,
+        height: img.height() as usize,
+        data: img.pixels().map(|pixel| pixel.0[0]).collect(),
+    }
+}
+
+fn with_alpha(img: &LumaA32FImage, plane: Plane) -> LumaA32FImage {
+    let buf = plane
+        .data
+        .iter()
+        .zip(img.pixels())
+        .flat_map(|(val, pixel)| [*val, pixel.0[1]])
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Float buffer should be sized correctly")
+}
+
+// Border constants are read in the image's own 0.0 - 1.0 units
+pub fn blur_luma_f32(img: &LumaA32FImage, sigma: f32, border: BorderMode) -> LumaA32FImage {
+    with_alpha(
+        img,
+        separable_blur(&luma_plane(img), &gaussian_kernel_1d(sigma), border),
+    )
+}
+
+// Premultiplied like the u8 blur, but nothing is clamped so HDR highlights survive
+pub fn blur_rgba_f32(img: &Rgba32FImage, sigma: f32, border: BorderMode) -> Rgba32FImage {
+    let kernel = gaussian_kernel_1d(sigma);
+
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let channel = |channel: usize| {
+        let plane = Plane {
+            width,
+            height,
+            data: img
+                .pixels()
+                .map(|pixel| pixel.0[channel] * pixel.0[3])
+                .collect(),
+        };
+        separable_blur(&plane, &kernel, border)
+    };
+
+    // A constant border reads as opaque
+    let alpha_border = match border {
+        BorderMode::Constant(_) => BorderMode::Constant(1.0),
+        other => other,
+    };
+
+    let alpha = separable_blur(
+        &Plane {
+            width,
+            height,
+            data: img.pixels().map(|pixel| pixel.0[3]).collect(),
+        },
+        &kernel,
+        alpha_border,
+    );
+    let (r, g, b) = (channel(0), channel(1), channel(2));
+
+    let mut buf = vec![0f32; width * height * 4];
+
+    for (idx, pixel) in buf.chunks_exact_mut(4).enumerate() {
+        let a = alpha.data[idx];
+        if a <= 0.0 {
+            continue;
+        }
+
+        pixel[0] = r.data[idx] / a;
+        pixel[1] = g.data[idx] / a;
+        pixel[2] = b.data[idx] / a;
+        pixel[3] = a;
+    }
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Blur buffer should be sized correctly")
+}
+
+// The raw response, negative values included, the cutoff is left to later steps
+pub fn dog_on_luma_f32(img: &LumaA32FImage, kernel_data: &GaussianKernelData) -> LumaA32FImage {
+    with_alpha(img, difference_of_gaussians(&luma_plane(img), kernel_data))
+}
+
+// Scaled to 0 - 255 first so the field is in the same Sobel units as the u8 path
+pub fn gradient_luma_f32(img: &LumaA32FImage, data: GradientData) -> GradientField {
+    let mut plane = luma_plane(img);
+    plane.data.iter_mut().for_each(|val| *val *= 255.0);
+
+    let (gx, gy) = gradients(&plane, data.operator, data.border);
+
+    GradientField {
+        width: plane.width,
+        height: plane.height,
+        gx: gx.data,
+        gy: gy.data,
+        luma: plane.data,
+        alpha: img
+            .pixels()
+            .map(|pixel| float_to_u8(pixel.0[1], (0.0, 1.0)))
+            .collect(),
+        border: data.border,
+    }
+}
+
+// Layers composited back to front, the last channel is alpha and colour is left unclamped
+//  Sizes are compared rather than lengths, a 2x8 and a 4x4 layer have the same length
+pub fn overlay_all_f32(layers: &[((u32, u32), &[f32])], channels: usize) -> Vec<f32> {
+    assert!(!layers.is_empty(), "Must have at least one layer");
+
+    let dimensions = layers[0].0;
+
+    for (layer_dimensions, layer) in layers.iter() {
+        assert!(
+            *layer_dimensions == dimensions,
+            "All layers must have same dimensions"
+        );
+        assert!(
+            layer.len() == dimensions.0 as usize * dimensions.1 as usize * channels,
+            "Layer buffer must match its dimensions"
+        );
+    }
+
+    let layers: Vec<&[f32]> = layers.iter().map(|(_, layer)| *layer).collect();
+
+    let mut out = vec![0f32; layers[0].len()];
+
+    out.par_chunks_mut(channels)
+        .enumerate()
+        .for_each(|(idx, pixel)| {
+            let start = idx * channels;
+            let mut acc_a = 0.0f32;
+
+            for layer in layers.iter() {
+                let fg = &layer[start..start + channels];
+                let alpha_fg = fg[channels - 1].clamp(0.0, 1.0);
+
+                if alpha_fg == 0.0 {
+                    continue;
+                }
+
+                let alpha_out = alpha_fg + acc_a * (1.0 - alpha_fg);
+
+                for (acc, val) in pixel.iter_mut().zip(fg.iter()).take(channels - 1) {
+                    *acc = (val * alpha_fg + *acc * acc_a * (1.0 - alpha_fg)) / alpha_out;
+                }
+                acc_a = alpha_out;
+            }
+
+            pixel[channels - 1] = acc_a;
+        });
+
+    out
+}
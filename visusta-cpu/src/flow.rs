@@ -0,0 +1,79 @@
+use visusta_core::{border::BorderMode, gaussians::gaussian_kernel_1d, gradient::GradientOperator};
+
+use crate::convolve::{Plane, gradients, separable_blur};
+
+// Per pixel orientation read from the smoothed structure tensor
+pub struct TangentFlow {
+    pub width: usize,
+    pub height: usize,
+    // Unit vectors along the edge, perpendicular to the gradient
+    pub tangents: Vec<(f32, f32)>,
+    // 0 for isotropic regions up to 1 for a strongly oriented edge
+    pub anisotropy: Vec<f32>,
+}
+
+impl TangentFlow {
+    pub fn from_plane(plane: &Plane, tensor_sigma: f32, border: BorderMode) -> TangentFlow {
+        let (gx, gy) = gradients(plane, GradientOperator::Sobel3, border);
+
+        let product = |a: &Plane, b: &Plane| Plane {
+            width: plane.width,
+            height: plane.height,
+            data: a
+                .data
+                .iter()
+                .zip(b.data.iter())
+                .map(|(a, b)| a * b)
+                .collect(),
+        };
+
+        let kernel = gaussian_kernel_1d(tensor_sigma);
+
+        let e = separable_blur(&product(&gx, &gx), &kernel, border);
+        let f = separable_blur(&product(&gx, &gy), &kernel, border);
+        let g = separable_blur(&product(&gy, &gy), &kernel, border);
+
+        let (tangents, anisotropy) = e
+            .data
+            .iter()
+            .zip(f.data.iter())
+            .zip(g.data.iter())
+            .map(|((e, f), g)| {
+                let root = ((e - g) * (e - g) + 4.0 * f * f).sqrt();
+                let major = (e + g + root) / 2.0;
+                let minor = (e + g - root) / 2.0;
+
+                // Eigenvector of the minor eigenvalue, runs along the edge
+                let (tx, ty) = (major - e, -f);
+                let len = (tx * tx + ty * ty).sqrt();
+
+                let tangent = if len > f32::EPSILON {
+                    (tx / len, ty / len)
+                } else {
+                    (0.0, 1.0)
+                };
+
+                let anisotropy = if major + minor > f32::EPSILON {
+                    (major - minor) / (major + minor)
+                } else {
+                    0.0
+                };
+
+                (tangent, anisotropy)
+            })
+            .unzip();
+
+        TangentFlow {
+            width: plane.width,
+            height: plane.height,
+            tangents,
+            anisotropy,
+        }
+    }
+
+    pub fn tangent_at(&self, x: f32, y: f32) -> (f32, f32) {
+        let x = (x.round() as isize).clamp(0, self.width as isize - 1) as usize;
+        let y = (y.round() as isize).clamp(0, self.height as isize - 1) as usize;
+        self.tangents[y * self.width + x]
+    }
+}
@@ -0,0 +1,104 @@
+use image::{ImageBuffer, RgbaImage};
+use visusta_core::{
+    LumaAImage,
+    border::BorderMode,
+    gaussians::{DogOutput, GaussianKernelData, gaussian_kernel_1d},
+};
+
+use crate::convolve::{Plane, difference_of_gaussians, separable_blur};
+
+pub fn blur_luma(img: &LumaAImage, sigma: f32, border: BorderMode) -> LumaAImage {
+    let blurred = separable_blur(&Plane::from_luma(img), &gaussian_kernel_1d(sigma), border);
+
+    let buf = blurred
+        .data
+        .iter()
+        .zip(img.pixels())
+        .flat_map(|(val, pixel)| [val.round().clamp(0.0, 255.0) as u8, pixel.0[1]])
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Blur buffer should be sized correctly")
+}
+
+// Colour channels are blurred premultiplied so transparent pixels don't bleed black
+pub fn blur_rgba(img: &RgbaImage, sigma: f32, border: BorderMode) -> RgbaImage {
+    let kernel = gaussian_kernel_1d(sigma);
+
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let channel = |channel: usize| {
+        let plane = Plane {
+            width,
+            height,
+            data: img
+                .pixels()
+                .map(|pixel| pixel.0[channel] as f32 * pixel.0[3] as f32 / 255.0)
+                .collect(),
+        };
+        separable_blur(&plane, &kernel, border)
+    };
+
+    // A constant border reads as opaque grey
+    let alpha_border = match border {
+        BorderMode::Constant(_) => BorderMode::Constant(255.0),
+        other => other,
+    };
+
+    let alpha = separable_blur(
+        &Plane {
+            width,
+            height,
+            data: img.pixels().map(|pixel| pixel.0[3] as f32).collect(),
+        },
+        &kernel,
+        alpha_border,
+    );
+    let (r, g, b) = (channel(0), channel(1), channel(2));
+
+    let mut buf = vec![0u8; width * height * 4];
+
+    for (idx, pixel) in buf.chunks_exact_mut(4).enumerate() {
+        let a = alpha.data[idx];
+        if a <= 0.0 {
+            continue;
+        }
+
+        let unpremultiply = |val: f32| (val * 255.0 / a).round().clamp(0.0, 255.0) as u8;
+
+        pixel[0] = unpremultiply(r.data[idx]);
+        pixel[1] = unpremultiply(g.data[idx]);
+        pixel[2] = unpremultiply(b.data[idx]);
+        pixel[3] = a.round().clamp(0.0, 255.0) as u8;
+    }
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Blur buffer should be sized correctly")
+}
+
+pub fn dog_on_luma(
+    img: &LumaAImage,
+    kernel_data: &GaussianKernelData,
+    output: DogOutput,
+) -> LumaAImage {
+    let response = difference_of_gaussians(&Plane::from_luma(img), kernel_data);
+
+    let buf = response
+        .data
+        .iter()
+        .zip(img.pixels())
+        .flat_map(|(val, pixel)| {
+            let mapped = match output {
+                DogOutput::Signed(gain) => 127.5 + val * gain,
+                DogOutput::Magnitude(gain) => val.abs() * gain,
+                DogOutput::Positive(gain) => val.max(0.0) * gain,
+            };
+
+            [mapped.round().clamp(0.0, 255.0) as u8, pixel.0[1]]
+        })
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("DoG buffer should be sized correctly")
+}
@@ -0,0 +1,29 @@
+use image::RgbaImage;
+use rayon::prelude::*;
+use visusta_core::{grade::ColourGrade, lut::LutData};
+
+fn map_rgb(img: &RgbaImage, op: impl Fn([f32; 3]) -> [f32; 3] + Sync) -> RgbaImage {
+    let mut out = img.clone();
+
+    out.par_chunks_mut(4).for_each(|pixel| {
+        let rgb = [pixel[0], pixel[1], pixel[2]].map(|val| val as f32 / 255.0);
+        let graded = op(rgb);
+
+        for channel in 0..3 {
+            pixel[channel] = (graded[channel] * 255.0).round().clamp(0.0, 255.0) as u8;
+        }
+    });
+
+    out
+}
+
+pub fn lut_on_rgba(img: &RgbaImage, data: &LutData) -> RgbaImage {
+    map_rgb(img, |rgb| {
+        let graded = data.lut.lookup(rgb, data.interpolation);
+        [0, 1, 2].map(|channel| rgb[channel] + (graded[channel] - rgb[channel]) * data.strength)
+    })
+}
+
+pub fn grade_on_rgba(img: &RgbaImage, grade: ColourGrade) -> RgbaImage {
+    map_rgb(img, |rgb| grade.apply(rgb))
+}
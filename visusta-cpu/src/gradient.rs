@@ -0,0 +1,62 @@
+use image::ImageBuffer;
+use visusta_core::{
+    LumaAImage,
+    canny::CannyThresholds,
+    gradient::{GradientData, GradientField, GradientMagnitude},
+};
+
+use crate::{
+    canny::edges_from_gradients,
+    convolve::{Plane, gradients},
+};
+
+pub fn gradient_on_luma(img: &LumaAImage, data: GradientData) -> GradientField {
+    let plane = Plane::from_luma(img);
+    let (gx, gy) = gradients(&plane, data.operator, data.border);
+
+    GradientField {
+        width: plane.width,
+        height: plane.height,
+        gx: gx.data,
+        gy: gy.data,
+        luma: plane.data,
+        alpha: img.pixels().map(|pixel| pixel.0[1]).collect(),
+        border: data.border,
+    }
+}
+
+pub fn gradient_to_luma(field: &GradientField, measure: GradientMagnitude) -> LumaAImage {
+    let buf = field
+        .gx
+        .iter()
+        .zip(field.gy.iter())
+        .zip(field.alpha.iter())
+        .flat_map(|((gx, gy), alpha)| [measure.normal(*gx, *gy), *alpha])
+        .collect();
+
+    ImageBuffer::from_raw(field.width as u32, field.height as u32, buf)
+        .expect("Gradient buffer should be sized correctly")
+}
+
+pub fn gradient_edges(field: &GradientField, thresholds: CannyThresholds) -> LumaAImage {
+    let plane = |data: &Vec<f32>| Plane {
+        width: field.width,
+        height: field.height,
+        data: data.clone(),
+    };
+
+    let edges = edges_from_gradients(
+        &plane(&field.gx),
+        &plane(&field.gy),
+        thresholds,
+        field.border,
+    );
+
+    let buf = edges
+        .iter()
+        .flat_map(|edge| if *edge { [255u8, 255u8] } else { [0u8, 0u8] })
+        .collect();
+
+    ImageBuffer::from_raw(field.width as u32, field.height as u32, buf)
+        .expect("Edge buffer should be sized correctly")
+}
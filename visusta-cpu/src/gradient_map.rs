@@ -0,0 +1,20 @@
+use image::{ImageBuffer, RgbaImage};
+use visusta_core::{LumaAImage, gradient_map::GradientMap};
+
+// Stop alpha is scaled by the input alpha, so transparent pixels stay transparent
+pub fn gradient_map_on_luma(img: &LumaAImage, map: &GradientMap) -> RgbaImage {
+    let lut = map.build_lut();
+
+    let buf = img
+        .pixels()
+        .flat_map(|pixel| {
+            let [luminance, alpha] = pixel.0;
+            let [r, g, b, a] = lut[luminance as usize];
+
+            [r, g, b, (a as u16 * alpha as u16 / 255) as u8]
+        })
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Gradient map buffer should be sized correctly")
+}
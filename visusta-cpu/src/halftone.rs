@@ -0,0 +1,200 @@
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::{
+    LumaAImage,
+    border::BorderMode,
+    gaussians::gaussian_kernel_1d,
+    halftone::{CmykHalftoneData, DotShape, HalftoneData},
+};
+
+use crate::convolve::{Plane, separable_blur};
+
+struct Screen {
+    cell_size: f32,
+    cos: f32,
+    sin: f32,
+    shape: DotShape,
+    anti_alias: bool,
+}
+
+impl Screen {
+    fn new(cell_size: f32, angle: f32, shape: DotShape, anti_alias: bool) -> Screen {
+        let radians = angle.to_radians();
+        Screen {
+            cell_size,
+            cos: radians.cos(),
+            sin: radians.sin(),
+            shape,
+            anti_alias,
+        }
+    }
+
+    // Ink coverage at a pixel, darkness is read from the blurred plane at the cell centre
+    fn coverage(&self, darkness: &Plane, x: usize, y: usize) -> f32 {
+        let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+        let u = (px * self.cos + py * self.sin) / self.cell_size;
+        let v = (-px * self.sin + py * self.cos) / self.cell_size;
+
+        let (cu, cv) = (u.floor() + 0.5, v.floor() + 0.5);
+
+        let cx = (cu * self.cos - cv * self.sin) * self.cell_size;
+        let cy = (cu * self.sin + cv * self.cos) * self.cell_size;
+
+        let amount = darkness.sample(cx - 0.5, cy - 0.5).clamp(0.0, 1.0);
+
+        // Signed distance to the dot edge in cell units, negative inside
+        let dist = dot_distance(self.shape, u - cu, v - cv, amount) * self.cell_size;
+
+        if self.anti_alias {
+            (0.5 - dist).clamp(0.0, 1.0)
+        } else if dist <= 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+// Dots are sized so the inked area of a cell matches its darkness
+fn dot_distance(shape: DotShape, u: f32, v: f32, amount: f32) -> f32 {
+    if amount <= 0.0 {
+        return f32::INFINITY;
+    }
+    if amount >= 1.0 {
+        return f32::NEG_INFINITY;
+    }
+
+    match shape {
+        DotShape::Round => {
+            // Past a quarter of pi the circle is clipped by the cell,
+            //  the radius runs on to the corners so a full cell is solid
+            let quarter = std::f32::consts::FRAC_PI_4;
+            let radius = if amount <= quarter {
+                (amount / std::f32::consts::PI).sqrt()
+            } else {
+                0.5 + (std::f32::consts::FRAC_1_SQRT_2 - 0.5) * (amount - quarter) / (1.0 - quarter)
+            };
+            (u * u + v * v).sqrt() - radius
+        }
+        DotShape::Line => v.abs() - amount / 2.0,
+        DotShape::Diamond => {
+            let reach = if amount <= 0.5 {
+                (amount / 2.0).sqrt()
+            } else {
+                1.0 - ((1.0 - amount) / 2.0).sqrt()
+            };
+            (u.abs() + v.abs() - reach) * std::f32::consts::FRAC_1_SQRT_2
+        }
+    }
+}
+
+// Roughly a box average over one cell so each dot follows its whole cell
+fn cell_average(plane: &Plane, cell_size: f32, border: BorderMode) -> Plane {
+    separable_blur(plane, &gaussian_kernel_1d(cell_size * 0.3), border)
+}
+
+// Planes hold 0 - 1 ink, a constant grey border is black ink only
+fn ink_border(border: BorderMode, black: bool) -> BorderMode {
+    match border {
+        BorderMode::Constant(val) if black => BorderMode::Constant(1.0 - val / 255.0),
+        BorderMode::Constant(_) => BorderMode::Constant(0.0),
+        border => border,
+    }
+}
+
+pub fn halftone_on_rgba(img: &RgbaImage, data: HalftoneData) -> LumaAImage {
+    let width = img.width() as usize;
+
+    let luminance = Plane::from_rgba_luminance(img);
+    let darkness = cell_average(
+        &Plane {
+            width: luminance.width,
+            height: luminance.height,
+            data: luminance.data.iter().map(|val| 1.0 - val / 255.0).collect(),
+        },
+        data.cell_size,
+        ink_border(data.border, true),
+    );
+
+    let screen = Screen::new(data.cell_size, data.angle, data.shape, data.anti_alias);
+
+    let mut buf = vec![0u8; width * img.height() as usize * 2];
+
+    buf.par_chunks_mut(width * 2)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(2).enumerate() {
+                let ink = screen.coverage(&darkness, x, y);
+                pixel[0] = ((1.0 - ink) * 255.0).round() as u8;
+                pixel[1] = img.get_pixel(x as u32, y as u32).0[3];
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Halftone buffer should be sized correctly")
+}
+
+pub fn halftone_cmyk_on_rgba(img: &RgbaImage, data: CmykHalftoneData) -> RgbaImage {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let mut separations: [Vec<f32>; 4] =
+        std::array::from_fn(|_| Vec::with_capacity(width * height));
+
+    for pixel in img.pixels() {
+        let [r, g, b, _] = pixel.0.map(|val| val as f32 / 255.0);
+        let k = 1.0 - r.max(g).max(b);
+
+        let (c, m, y) = if k >= 1.0 {
+            (0.0, 0.0, 0.0)
+        } else {
+            (
+                (1.0 - r - k) / (1.0 - k),
+                (1.0 - g - k) / (1.0 - k),
+                (1.0 - b - k) / (1.0 - k),
+            )
+        };
+
+        for (separation, val) in separations.iter_mut().zip([c, m, y, k]) {
+            separation.push(val);
+        }
+    }
+
+    let inks: Vec<(Plane, Screen)> = separations
+        .into_par_iter()
+        .zip(data.angles)
+        .enumerate()
+        .map(|(idx, (separation, angle))| {
+            let plane = Plane {
+                width,
+                height,
+                data: separation,
+            };
+            (
+                cell_average(&plane, data.cell_size, ink_border(data.border, idx == 3)),
+                Screen::new(data.cell_size, angle, data.shape, data.anti_alias),
+            )
+        })
+        .collect();
+
+    let mut buf = img.as_raw().clone();
+
+    buf.par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let [c, m, yellow, k] =
+                    std::array::from_fn(|idx| inks[idx].1.coverage(&inks[idx].0, x, y));
+
+                // Inks multiply onto white paper
+                let paper = 1.0 - k;
+                for (val, ink) in pixel.iter_mut().zip([c, m, yellow]) {
+                    *val = ((1.0 - ink) * paper * 255.0).round() as u8;
+                }
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Halftone buffer should be sized correctly")
+}
@@ -0,0 +1,134 @@
+use std::f32::consts::PI;
+
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::{
+    LumaAImage,
+    gaussians::gaussian_kernel_1d,
+    gradient::{GradientField, GradientMagnitude, GradientOperator},
+    hatch::{HatchData, HatchOrientation},
+};
+
+use crate::{
+    DirectionAscii,
+    convolve::{Plane, gradients, separable_blur},
+    sobel_dir_gx_gy,
+};
+
+// Each extra layer crosses the ones before it
+const LAYER_OFFSETS: [f32; 4] = [0.0, PI / 2.0, PI / 4.0, -PI / 4.0];
+
+pub fn hatch_on_rgba(img: &RgbaImage, data: &HatchData, paper: Option<&RgbaImage>) -> LumaAImage {
+    let alpha = img.pixels().map(|pixel| pixel.0[3]).collect();
+    hatch_on_plane(&Plane::from_rgba_luminance(img), alpha, data, paper)
+}
+
+pub fn hatch_on_luma(img: &LumaAImage, data: &HatchData, paper: Option<&RgbaImage>) -> LumaAImage {
+    let alpha = img.pixels().map(|pixel| pixel.0[1]).collect();
+    hatch_on_plane(&Plane::from_luma(img), alpha, data, paper)
+}
+
+fn hatch_on_plane(
+    plane: &Plane,
+    alpha: Vec<u8>,
+    data: &HatchData,
+    paper: Option<&RgbaImage>,
+) -> LumaAImage {
+    // Gradients from a lightly blurred copy so the angle holds steady over a stroke
+    let (gx, gy) = gradients(
+        &separable_blur(plane, &gaussian_kernel_1d(1.5), data.border),
+        GradientOperator::Sobel3,
+        data.border,
+    );
+
+    let field = GradientField {
+        width: plane.width,
+        height: plane.height,
+        gx: gx.data,
+        gy: gy.data,
+        luma: plane.data.clone(),
+        alpha,
+        border: data.border,
+    };
+
+    hatch_on_field(&field, data, paper)
+}
+
+// Bands come from the field's luminance and stroke angles from its gradients,
+//  the paper has already been fitted to the field by the pipeline
+pub fn hatch_on_field(
+    field: &GradientField,
+    data: &HatchData,
+    paper: Option<&RgbaImage>,
+) -> LumaAImage {
+    let width = field.width;
+    let paper = paper.map(Plane::from_rgba_luminance);
+
+    let band_size = 256.0 / data.levels as f32;
+    let default_angle = data.default_angle.to_radians();
+
+    let mut buf = vec![0u8; width * field.height * 2];
+
+    buf.par_chunks_mut(width * 2)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(2).enumerate() {
+                let idx = y * width + x;
+
+                // Band 0 is the lightest and is left as paper
+                let band =
+                    data.levels - 1 - ((field.luma[idx] / band_size) as u8).min(data.levels - 1);
+                let active = band.min(data.layers) as usize;
+
+                let (gx, gy) = (field.gx[idx], field.gy[idx]);
+                let normal = GradientMagnitude::Squared.normal(gx, gy);
+
+                let gradient_angle = if normal >= data.magnitude_min {
+                    direction_angle(sobel_dir_gx_gy(gx, gy))
+                } else {
+                    None
+                };
+
+                let stroke_angle = match (gradient_angle, data.orientation) {
+                    (Some(angle), HatchOrientation::Gradient) => angle,
+                    (Some(angle), HatchOrientation::Perpendicular) => angle + PI / 2.0,
+                    (None, _) => default_angle,
+                };
+
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+                let mut value = 1.0f32;
+                for offset in LAYER_OFFSETS.iter().take(active) {
+                    // Stripes are laid out in image space so neighbouring pixels line up
+                    let angle = stroke_angle + offset;
+                    let across = -px * angle.sin() + py * angle.cos();
+                    let phase = (across / data.spacing).rem_euclid(1.0) - 0.5;
+                    let dist = phase.abs() * data.spacing;
+
+                    let coverage = (data.thickness / 2.0 - dist + 0.5).clamp(0.0, 1.0);
+                    value *= 1.0 - coverage;
+                }
+
+                if let Some(paper) = paper.as_ref() {
+                    value *= paper.data[idx] / 255.0;
+                }
+
+                pixel[0] = (value * 255.0).round() as u8;
+                pixel[1] = field.alpha[idx];
+            }
+        });
+
+    ImageBuffer::from_raw(field.width as u32, field.height as u32, buf)
+        .expect("Hatch buffer should be sized correctly")
+}
+
+// Gradient angle for each of the quantised Sobel directions
+fn direction_angle(direction: DirectionAscii) -> Option<f32> {
+    match direction {
+        DirectionAscii::None => None,
+        DirectionAscii::X => Some(0.0),
+        DirectionAscii::LR => Some(PI / 4.0),
+        DirectionAscii::Y => Some(PI / 2.0),
+        DirectionAscii::RL => Some(3.0 * PI / 4.0),
+    }
+}
@@ -0,0 +1,242 @@
+use image::ImageBuffer;
+use rayon::prelude::*;
+use visusta_core::{
+    LumaAImage,
+    histogram::{AutoContrast, ClaheData, HistogramFilter},
+    tone::ToneLut,
+};
+
+type Histogram = [u32; 256];
+
+pub fn histogram_on_luma(img: &LumaAImage, filter: HistogramFilter) -> LumaAImage {
+    match filter {
+        HistogramFilter::Equalize => {
+            let lut = equalize_lut(&luma_histogram(img));
+            map_luma(img, |_, _, val| lut[val as usize])
+        }
+        HistogramFilter::AutoContrast(data) => {
+            let lut = auto_contrast_lut(&luma_histogram(img), data);
+            map_luma(img, |_, _, val| lut[val as usize])
+        }
+        HistogramFilter::Clahe(data) => clahe(img, data),
+    }
+}
+
+// Only counts visible pixels so transparent regions don't skew the distribution
+pub fn luma_histogram(img: &LumaAImage) -> Histogram {
+    let mut hist = [0u32; 256];
+
+    for pixel in img.pixels() {
+        if pixel.0[1] > 0 {
+            hist[pixel.0[0] as usize] += 1;
+        }
+    }
+
+    hist
+}
+
+fn map_luma(img: &LumaAImage, map: impl Fn(u32, u32, u8) -> u8 + Sync) -> LumaAImage {
+    let width = img.width() as usize;
+
+    let mut buf = img.as_raw().clone();
+
+    buf.par_chunks_mut(width * 2)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(2).enumerate() {
+                if pixel[1] == 0 {
+                    continue;
+                }
+                pixel[0] = map(x as u32, y as u32, pixel[0]);
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Histogram buffer should be sized correctly")
+}
+
+fn equalize_lut(hist: &Histogram) -> ToneLut {
+    let mut lut: ToneLut = std::array::from_fn(|idx| idx as u8);
+
+    let total: u32 = hist.iter().sum();
+    let Some(cdf_min) = hist.iter().copied().find(|count| *count > 0) else {
+        return lut;
+    };
+
+    if total == cdf_min {
+        return lut;
+    }
+
+    let mut cdf = 0u32;
+    for (idx, count) in hist.iter().enumerate() {
+        cdf += count;
+        let normal = cdf.saturating_sub(cdf_min) as f32 / (total - cdf_min) as f32;
+        lut[idx] = (normal * 255.0).round() as u8;
+    }
+
+    lut
+}
+
+fn auto_contrast_lut(hist: &Histogram, data: AutoContrast) -> ToneLut {
+    let identity: ToneLut = std::array::from_fn(|idx| idx as u8);
+
+    let total: u32 = hist.iter().sum();
+    if total == 0 {
+        return identity;
+    }
+
+    let percentile_value = |fraction: f32| {
+        let target = (fraction * total as f32).ceil().max(1.0) as u32;
+        let mut cumulative = 0u32;
+
+        for (idx, count) in hist.iter().enumerate() {
+            cumulative += count;
+            if cumulative >= target {
+                return idx as f32;
+            }
+        }
+
+        255.0
+    };
+
+    let low = percentile_value(data.low);
+    let high = percentile_value(data.high);
+
+    if high <= low {
+        return identity;
+    }
+
+    std::array::from_fn(|idx| {
+        (((idx as f32 - low) / (high - low)).clamp(0.0, 1.0) * 255.0).round() as u8
+    })
+}
+
+fn clahe(img: &LumaAImage, data: ClaheData) -> LumaAImage {
+    let width = img.width();
+    let height = img.height();
+
+    let tile_w = width.div_ceil(data.tiles_x.min(width.max(1))).max(1);
+    let tile_h = height.div_ceil(data.tiles_y.min(height.max(1))).max(1);
+
+    // Rounding the tile size up can leave trailing tiles with no pixels, so count
+    //  the tiles that actually cover the image
+    let tiles_x = width.div_ceil(tile_w).max(1);
+    let tiles_y = height.div_ceil(tile_h).max(1);
+
+    let tile_luts: Vec<ToneLut> = (0..tiles_x * tiles_y)
+        .into_par_iter()
+        .map(|tile_idx| {
+            let x_start = (tile_idx % tiles_x) * tile_w;
+            let y_start = (tile_idx / tiles_x) * tile_h;
+
+            let mut hist = [0u32; 256];
+            for y in y_start..(y_start + tile_h).min(height) {
+                for x in x_start..(x_start + tile_w).min(width) {
+                    let pixel = img.get_pixel(x, y).0;
+                    if pixel[1] > 0 {
+                        hist[pixel[0] as usize] += 1;
+                    }
+                }
+            }
+
+            clipped_tile_lut(&mut hist, data.clip_limit)
+        })
+        .collect();
+
+    // Tile centres sit at (i + 0.5) * tile size, each pixel blends the 4 nearest
+    let tile_position = |pos: u32, size: u32, count: u32| {
+        let centred = ((pos as f32 + 0.5) / size as f32 - 0.5).clamp(0.0, (count - 1) as f32);
+        let low = centred.floor() as u32;
+        let high = (low + 1).min(count - 1);
+        (low, high, centred - low as f32)
+    };
+
+    map_luma(img, |x, y, val| {
+        let (x0, x1, wx) = tile_position(x, tile_w, tiles_x);
+        let (y0, y1, wy) = tile_position(y, tile_h, tiles_y);
+
+        let lookup =
+            |tx: u32, ty: u32| tile_luts[(ty * tiles_x + tx) as usize][val as usize] as f32;
+
+        let top = lookup(x0, y0) * (1.0 - wx) + lookup(x1, y0) * wx;
+        let bottom = lookup(x0, y1) * (1.0 - wx) + lookup(x1, y1) * wx;
+
+        (top * (1.0 - wy) + bottom * wy).round().clamp(0.0, 255.0) as u8
+    })
+}
+
+fn clipped_tile_lut(hist: &mut Histogram, clip_limit: f32) -> ToneLut {
+    let total: u32 = hist.iter().sum();
+    if total == 0 {
+        return std::array::from_fn(|idx| idx as u8);
+    }
+
+    let limit = ((clip_limit * total as f32) / 256.0).max(1.0) as u32;
+
+    let mut excess = 0u32;
+    for count in hist.iter_mut() {
+        if *count > limit {
+            excess += *count - limit;
+            *count = limit;
+        }
+    }
+
+    let spread = excess / 256;
+    let remainder = (excess % 256) as usize;
+    for (idx, count) in hist.iter_mut().enumerate() {
+        *count += spread + u32::from(idx < remainder);
+    }
+
+    let mut lut = [0u8; 256];
+    let mut cdf = 0u32;
+    for (idx, count) in hist.iter().enumerate() {
+        cdf += count;
+        lut[idx] = ((cdf as f32 / total as f32) * 255.0).round().min(255.0) as u8;
+    }
+
+    lut
+}
+
+// Threshold maximising the between class variance of the histogram
+pub fn otsu_threshold(hist: &Histogram) -> u8 {
+    let total: u64 = hist.iter().map(|count| *count as u64).sum();
+    if total == 0 {
+        return 0;
+    }
+
+    let weighted_total: f64 = hist
+        .iter()
+        .enumerate()
+        .map(|(idx, count)| idx as f64 * *count as f64)
+        .sum();
+
+    let mut best = (0u8, 0f64);
+    let mut background = 0u64;
+    let mut weighted_background = 0f64;
+
+    for (idx, count) in hist.iter().enumerate() {
+        background += *count as u64;
+        if background == 0 {
+            continue;
+        }
+
+        let foreground = total - background;
+        if foreground == 0 {
+            break;
+        }
+
+        weighted_background += idx as f64 * *count as f64;
+
+        let mean_background = weighted_background / background as f64;
+        let mean_foreground = (weighted_total - weighted_background) / foreground as f64;
+
+        let diff = mean_background - mean_foreground;
+        let variance = background as f64 * foreground as f64 * diff * diff;
+
+        if variance > best.1 {
+            best = (idx as u8, variance);
+        }
+    }
+
+    best.0
+}
@@ -0,0 +1,252 @@
+use std::f32::consts::PI;
+
+use image::{ImageBuffer, RgbaImage};
+use libm::atan2f;
+use rayon::prelude::*;
+use visusta_core::{
+    border::BorderMode,
+    kuwahara::{KuwaharaData, KuwaharaMode},
+};
+
+use crate::{convolve::Plane, flow::TangentFlow};
+
+const SECTORS: usize = 8;
+
+pub fn kuwahara_on_rgba(img: &RgbaImage, data: KuwaharaData) -> RgbaImage {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let flow = match data.mode {
+        KuwaharaMode::Anisotropic(anisotropic) => Some(TangentFlow::from_plane(
+            &Plane::from_rgba_luminance(img),
+            anisotropic.tensor_sigma,
+            data.border,
+        )),
+        _ => None,
+    };
+
+    let mut buf = vec![0u8; width * height * 4];
+
+    buf.par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for x in 0..width {
+                let colour = match data.mode {
+                    KuwaharaMode::Classic => classic(img, x, y, data.radius as isize, data.border),
+                    KuwaharaMode::Generalized => {
+                        let ellipse = Ellipse::circle(data.radius as f32);
+                        sectors(img, x, y, &ellipse, data.sharpness, data.border)
+                    }
+                    KuwaharaMode::Anisotropic(anisotropic) => {
+                        let flow = flow.as_ref().expect("Anisotropic mode computes a flow");
+                        let idx = y * width + x;
+                        let ellipse = Ellipse::oriented(
+                            data.radius as f32,
+                            anisotropic.alpha,
+                            flow.anisotropy[idx],
+                            flow.tangents[idx],
+                        );
+                        sectors(img, x, y, &ellipse, data.sharpness, data.border)
+                    }
+                };
+
+                let out_idx = x * 4;
+                for channel in 0..3 {
+                    row[out_idx + channel] = colour[channel].round().clamp(0.0, 255.0) as u8;
+                }
+                row[out_idx + 3] = img.get_pixel(x as u32, y as u32).0[3];
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Kuwahara buffer should be sized correctly")
+}
+
+#[derive(Default, Clone, Copy)]
+struct Moments {
+    weight: f32,
+    sum: [f32; 3],
+    sum_sq: [f32; 3],
+}
+
+impl Moments {
+    fn add(&mut self, pixel: &[u8], weight: f32) {
+        self.weight += weight;
+        for (channel, val) in pixel.iter().take(3).enumerate() {
+            let val = *val as f32;
+            self.sum[channel] += weight * val;
+            self.sum_sq[channel] += weight * val * val;
+        }
+    }
+
+    fn mean(&self) -> [f32; 3] {
+        self.sum.map(|sum| sum / self.weight)
+    }
+
+    // Summed over the colour channels
+    fn variance(&self) -> f32 {
+        let mean = self.mean();
+        (0..3)
+            .map(|channel| {
+                (self.sum_sq[channel] / self.weight - mean[channel] * mean[channel]).max(0.0)
+            })
+            .sum()
+    }
+}
+
+fn classic(img: &RgbaImage, x: usize, y: usize, radius: isize, border: BorderMode) -> [f32; 3] {
+    let (x, y) = (x as isize, y as isize);
+
+    let quadrants = [
+        (x - radius..=x, y - radius..=y),
+        (x..=x + radius, y - radius..=y),
+        (x - radius..=x, y..=y + radius),
+        (x..=x + radius, y..=y + radius),
+    ];
+
+    quadrants
+        .into_iter()
+        .map(|(xs, ys)| {
+            let mut moments = Moments::default();
+
+            for qy in ys.clone() {
+                for qx in xs.clone() {
+                    moments.add(&border_pixel(img, qx, qy, border), 1.0);
+                }
+            }
+
+            moments
+        })
+        .min_by(|a, b| a.variance().total_cmp(&b.variance()))
+        .expect("There are always 4 quadrants")
+        .mean()
+}
+
+// Reflect is the default, a clamped or clipped region can flatten to the edge pixel
+//  with no variance and always win
+fn border_pixel(img: &RgbaImage, x: isize, y: isize, border: BorderMode) -> [u8; 4] {
+    match (
+        border.index(x, img.width() as usize),
+        border.index(y, img.height() as usize),
+    ) {
+        (Some(x), Some(y)) => img.get_pixel(x as u32, y as u32).0,
+        _ => {
+            let val = border.constant().round().clamp(0.0, 255.0) as u8;
+            [val, val, val, 255]
+        }
+    }
+}
+
+struct Ellipse {
+    major: f32,
+    minor: f32,
+    tangent: (f32, f32),
+}
+
+impl Ellipse {
+    fn circle(radius: f32) -> Ellipse {
+        Ellipse {
+            major: radius,
+            minor: radius,
+            tangent: (1.0, 0.0),
+        }
+    }
+
+    // Kyprianidis et al. stretch along the tangent by (alpha + A) / alpha
+    fn oriented(radius: f32, alpha: f32, anisotropy: f32, tangent: (f32, f32)) -> Ellipse {
+        Ellipse {
+            major: radius * (alpha + anisotropy) / alpha,
+            minor: radius * alpha / (alpha + anisotropy),
+            tangent,
+        }
+    }
+
+    fn extents(&self) -> (isize, isize) {
+        let (tx, ty) = self.tangent;
+        let (a, b) = (self.major, self.minor);
+
+        (
+            (a * a * tx * tx + b * b * ty * ty).sqrt().ceil() as isize,
+            (a * a * ty * ty + b * b * tx * tx).sqrt().ceil() as isize,
+        )
+    }
+
+    // Offset in the unit disc, u along the tangent
+    fn to_unit(&self, dx: f32, dy: f32) -> (f32, f32) {
+        let (tx, ty) = self.tangent;
+        (
+            (dx * tx + dy * ty) / self.major,
+            (-dx * ty + dy * tx) / self.minor,
+        )
+    }
+}
+
+fn sectors(
+    img: &RgbaImage,
+    x: usize,
+    y: usize,
+    ellipse: &Ellipse,
+    sharpness: f32,
+    border: BorderMode,
+) -> [f32; 3] {
+    let (x, y) = (x as isize, y as isize);
+
+    let sector_width = 2.0 * PI / SECTORS as f32;
+    let angular_sigma = sector_width / 2.0;
+
+    let mut moments = [Moments::default(); SECTORS];
+
+    let (extent_x, extent_y) = ellipse.extents();
+
+    for dy in -extent_y..=extent_y {
+        for dx in -extent_x..=extent_x {
+            let (u, v) = ellipse.to_unit(dx as f32, dy as f32);
+            let dist_sq = u * u + v * v;
+            if dist_sq > 1.0 {
+                continue;
+            }
+
+            let pixel = border_pixel(img, x + dx, y + dy, border);
+            let radial = (-2.0 * dist_sq).exp();
+
+            if dx == 0 && dy == 0 {
+                for sector in moments.iter_mut() {
+                    sector.add(&pixel, radial);
+                }
+                continue;
+            }
+
+            let angle = atan2f(v, u);
+
+            // Sectors overlap their neighbours with a Gaussian falloff
+            for (idx, sector) in moments.iter_mut().enumerate() {
+                let mut delta = angle - idx as f32 * sector_width;
+                delta = (delta + PI).rem_euclid(2.0 * PI) - PI;
+
+                if delta.abs() >= sector_width {
+                    continue;
+                }
+
+                let angular = (-(delta * delta) / (2.0 * angular_sigma * angular_sigma)).exp();
+                sector.add(&pixel, radial * angular);
+            }
+        }
+    }
+
+    let mut total_weight = 0f32;
+    let mut colour = [0f32; 3];
+
+    for sector in moments.iter().filter(|sector| sector.weight > 0.0) {
+        // Variance is on a 0 - 255² scale, over 255 it equals the reference implementation's
+        // 255 * variance of 0 - 1 colours
+        let weight = 1.0 / (1.0 + (sector.variance() / 255.0).powf(sharpness / 2.0));
+        let mean = sector.mean();
+
+        total_weight += weight;
+        for channel in 0..3 {
+            colour[channel] += weight * mean[channel];
+        }
+    }
+
+    colour.map(|val| val / total_weight)
+}
@@ -1,16 +1,95 @@
 use std::f32::consts::PI;
 
 use async_trait::async_trait;
-use image::{DynamicImage, ImageBuffer, RgbaImage};
+use image::{DynamicImage, ImageBuffer, Rgba32FImage, RgbaImage};
 use libm::atan2f;
 use rayon::prelude::*;
 use visusta_core::{
-    CharImage, LumaAImage, LuminanceAsciiFilter, LuminanceFilter, SobelAscii, SobelColorData,
-    SobelColorItem, VisustaProcessor,
-    gaussians::{GaussianBuilder, GaussianColorData, GaussianColorItem, GaussianKernelData},
+    CharImage, GradientAsciiData, GradientColourData, LumaA32FImage, LumaAImage,
+    LuminanceAsciiFilter, LuminanceFilter, SobelAscii, SobelColorData, SobelColouring,
+    VisustaProcessor,
+    auxiliary::{BlendData, SizePolicy},
+    border::BorderMode,
+    canny::{CannyData, CannyThresholds},
+    channel::{ChannelMapping, ChannelSample},
+    effects::{BloomData, GrainData, VignetteData},
+    float::FloatRange,
+    gaussians::DogOutput,
+    gaussians::{GaussianBuilder, GaussianKernelData},
+    grade::ColourGrade,
+    gradient::{GradientData, GradientField, GradientMagnitude},
+    gradient_map::GradientMap,
+    halftone::{CmykHalftoneData, HalftoneData},
+    hatch::HatchData,
+    histogram::HistogramFilter,
+    kuwahara::KuwaharaData,
+    lowpoly::{LowPolyData, TriangleMesh},
+    lut::LutData,
+    mask::{LuminanceMask, MaskData},
+    morphology::MorphologyOp,
+    mosaic::{MosaicData, TileGrid},
     pipeline::LayerOutput,
+    pixelsort::PixelSortData,
+    threshold::{Cutoff, PosterizeData, ThresholdFilter},
+    tone::ToneFilter,
+    voronoi::{StainedGlassData, StippleData, StippleSet},
+    xdog::XDogData,
 };
 
+use crate::auxiliary::{blend_rgba, fit_rgba};
+use crate::canny::canny_on_luma;
+use crate::convolve::{Plane, difference_of_gaussians};
+use crate::effects::{bloom_on_rgba, grain_on_rgba, vignette_on_rgba};
+use crate::float::{
+    blur_luma_f32, blur_rgba_f32, dog_on_luma_f32, gradient_luma_f32, luma_from_float,
+    luma_to_float, overlay_all_f32, rgba_from_float, rgba_to_float, rgba_to_luma_f32,
+};
+use crate::gaussians::{blur_luma, blur_rgba, dog_on_luma};
+use crate::grade::{grade_on_rgba, lut_on_rgba};
+use crate::gradient::{gradient_edges, gradient_on_luma, gradient_to_luma};
+use crate::gradient_map::gradient_map_on_luma;
+use crate::halftone::{halftone_cmyk_on_rgba, halftone_on_rgba};
+use crate::hatch::{hatch_on_field, hatch_on_luma, hatch_on_rgba};
+use crate::histogram::{histogram_on_luma, otsu_threshold};
+use crate::kuwahara::kuwahara_on_rgba;
+use crate::lowpoly::{low_poly_mesh, low_poly_on_rgba};
+use crate::mask::{apply_mask, luminance_mask};
+use crate::morphology::morphology_on_luma;
+use crate::mosaic::{mosaic_on_rgba, mosaic_with_grid};
+use crate::pixelsort::pixel_sort_on_rgba;
+use crate::stained_glass::stained_glass_on_rgba;
+use crate::stipple::{stipple_on_rgba, stipple_points};
+use crate::threshold::{otsu_cutoff, posterize_on_rgba, threshold_on_luma};
+use crate::tone::{tone_on_luma, tone_on_rgba};
+use crate::xdog::xdog_on_luma;
+
+mod auxiliary;
+mod canny;
+mod convolve;
+mod delaunay;
+mod effects;
+mod float;
+mod flow;
+mod gaussians;
+mod grade;
+mod gradient;
+mod gradient_map;
+mod halftone;
+mod hatch;
+mod histogram;
+mod kuwahara;
+mod lowpoly;
+mod mask;
+mod morphology;
+mod mosaic;
+mod pixelsort;
+mod stained_glass;
+mod stipple;
+mod threshold;
+mod tone;
+mod voronoi;
+mod xdog;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum DirectionAscii {
     None,
@@ -32,8 +111,13 @@ impl VisustaProcessor for VisustaCPU {
         DynamicImage::from(img.clone()).to_rgba8()
     }
 
-    async fn sobel_to_colour(&self, img: &LumaAImage, filter: SobelColorData) -> RgbaImage {
-        page_to_direction_colour(img, filter)
+    async fn sobel_to_colour(
+        &self,
+        img: &LumaAImage,
+        filter: SobelColorData,
+        original: Option<&RgbaImage>,
+    ) -> RgbaImage {
+        page_to_direction_colour(img, filter, original)
     }
 
     async fn gaussian_on_luma(&self, img: &LumaAImage, builder: GaussianBuilder) -> LumaAImage {
@@ -46,11 +130,12 @@ impl VisustaProcessor for VisustaCPU {
         &self,
         img: &LumaAImage,
         builder: GaussianBuilder,
-        filter: GaussianColorData,
+        channels: ChannelMapping,
+        original: Option<&RgbaImage>,
     ) -> RgbaImage {
         let kernel_data = builder.build_kernel();
 
-        gaussian_to_coloured(img, kernel_data, filter)
+        gaussian_to_coloured(img, kernel_data, channels, original)
     }
 
     async fn luminance_to_ascii(
@@ -65,6 +150,259 @@ impl VisustaProcessor for VisustaCPU {
         sobel_ascii_directional(img, filter)
     }
 
+    async fn tone_rgba(&self, img: &RgbaImage, filter: ToneFilter) -> RgbaImage {
+        tone_on_rgba(img, &filter.build_luts())
+    }
+
+    async fn tone_luma(&self, img: &LumaAImage, filter: ToneFilter) -> LumaAImage {
+        tone_on_luma(img, &filter.build_luts())
+    }
+
+    async fn histogram_luma(&self, img: &LumaAImage, filter: HistogramFilter) -> LumaAImage {
+        histogram_on_luma(img, filter)
+    }
+
+    async fn canny_on_luma(&self, img: &LumaAImage, data: CannyData) -> LumaAImage {
+        canny_on_luma(img, data)
+    }
+
+    async fn blur_luma(&self, img: &LumaAImage, sigma: f32, border: BorderMode) -> LumaAImage {
+        blur_luma(img, sigma, border)
+    }
+
+    async fn blur_rgba(&self, img: &RgbaImage, sigma: f32, border: BorderMode) -> RgbaImage {
+        blur_rgba(img, sigma, border)
+    }
+
+    async fn dog_on_luma(
+        &self,
+        img: &LumaAImage,
+        builder: GaussianBuilder,
+        output: DogOutput,
+    ) -> LumaAImage {
+        dog_on_luma(img, &builder.build_kernel(), output)
+    }
+
+    async fn xdog_on_luma(&self, img: &LumaAImage, data: XDogData) -> LumaAImage {
+        xdog_on_luma(img, data)
+    }
+
+    async fn kuwahara_rgba(&self, img: &RgbaImage, data: KuwaharaData) -> RgbaImage {
+        kuwahara_on_rgba(img, data)
+    }
+
+    async fn grain_rgba(&self, img: &RgbaImage, data: GrainData) -> RgbaImage {
+        grain_on_rgba(img, data)
+    }
+
+    async fn vignette_rgba(&self, img: &RgbaImage, data: VignetteData) -> RgbaImage {
+        vignette_on_rgba(img, data)
+    }
+
+    async fn bloom_rgba(&self, img: &RgbaImage, data: BloomData) -> RgbaImage {
+        bloom_on_rgba(img, data)
+    }
+
+    async fn mosaic_rgba(&self, img: &RgbaImage, data: MosaicData) -> RgbaImage {
+        mosaic_on_rgba(img, &data)
+    }
+
+    async fn mosaic_rgba_grid(&self, img: &RgbaImage, data: MosaicData) -> (RgbaImage, TileGrid) {
+        mosaic_with_grid(img, &data)
+    }
+
+    async fn low_poly_rgba(&self, img: &RgbaImage, data: LowPolyData) -> RgbaImage {
+        low_poly_on_rgba(img, data)
+    }
+
+    async fn low_poly_mesh(&self, img: &RgbaImage, data: LowPolyData) -> TriangleMesh {
+        low_poly_mesh(img, data)
+    }
+
+    async fn stained_glass_rgba(&self, img: &RgbaImage, data: StainedGlassData) -> RgbaImage {
+        stained_glass_on_rgba(img, data)
+    }
+
+    async fn stipple_rgba(&self, img: &RgbaImage, data: StippleData) -> RgbaImage {
+        stipple_on_rgba(img, data)
+    }
+
+    async fn stipple_points(&self, img: &RgbaImage, data: StippleData) -> StippleSet {
+        stipple_points(img, data)
+    }
+
+    async fn halftone_rgba(&self, img: &RgbaImage, data: HalftoneData) -> LumaAImage {
+        halftone_on_rgba(img, data)
+    }
+
+    async fn halftone_cmyk(&self, img: &RgbaImage, data: CmykHalftoneData) -> RgbaImage {
+        halftone_cmyk_on_rgba(img, data)
+    }
+
+    async fn pixel_sort_rgba(&self, img: &RgbaImage, data: PixelSortData) -> RgbaImage {
+        pixel_sort_on_rgba(img, data)
+    }
+
+    async fn hatch_rgba(
+        &self,
+        img: &RgbaImage,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage {
+        hatch_on_rgba(img, &data, paper)
+    }
+
+    async fn hatch_luma(
+        &self,
+        img: &LumaAImage,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage {
+        hatch_on_luma(img, &data, paper)
+    }
+
+    async fn threshold_luma(&self, img: &LumaAImage, filter: ThresholdFilter) -> LumaAImage {
+        threshold_on_luma(img, filter)
+    }
+
+    async fn posterize_rgba(&self, img: &RgbaImage, data: PosterizeData) -> RgbaImage {
+        posterize_on_rgba(img, data)
+    }
+
+    async fn morphology_luma(&self, img: &LumaAImage, op: MorphologyOp) -> LumaAImage {
+        morphology_on_luma(img, op)
+    }
+
+    async fn gradient_luma(&self, img: &LumaAImage, data: GradientData) -> GradientField {
+        gradient_on_luma(img, data)
+    }
+
+    async fn gradient_to_colour(
+        &self,
+        field: &GradientField,
+        filter: GradientColourData,
+        original: Option<&RgbaImage>,
+    ) -> RgbaImage {
+        gradient_to_colour(field, &filter, original)
+    }
+
+    async fn gradient_to_luma(
+        &self,
+        field: &GradientField,
+        measure: GradientMagnitude,
+    ) -> LumaAImage {
+        gradient_to_luma(field, measure)
+    }
+
+    async fn gradient_hatch(
+        &self,
+        field: &GradientField,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage {
+        hatch_on_field(field, &data, paper)
+    }
+
+    async fn gradient_edges(
+        &self,
+        field: &GradientField,
+        thresholds: CannyThresholds,
+    ) -> LumaAImage {
+        gradient_edges(field, thresholds)
+    }
+
+    async fn gradient_to_ascii(
+        &self,
+        field: &GradientField,
+        filter: GradientAsciiData,
+    ) -> CharImage {
+        gradient_to_ascii(field, &filter)
+    }
+
+    async fn rgba_to_float(&self, img: &RgbaImage) -> Rgba32FImage {
+        rgba_to_float(img)
+    }
+
+    async fn rgba_from_float(&self, img: &Rgba32FImage, range: FloatRange) -> RgbaImage {
+        rgba_from_float(img, range)
+    }
+
+    async fn luma_to_float(&self, img: &LumaAImage) -> LumaA32FImage {
+        luma_to_float(img)
+    }
+
+    async fn luma_from_float(&self, img: &LumaA32FImage, range: FloatRange) -> LumaAImage {
+        luma_from_float(img, range)
+    }
+
+    async fn rgba_to_luma_f32(&self, img: &Rgba32FImage, filter: LuminanceFilter) -> LumaA32FImage {
+        rgba_to_luma_f32(img, filter)
+    }
+
+    async fn blur_rgba_f32(
+        &self,
+        img: &Rgba32FImage,
+        sigma: f32,
+        border: BorderMode,
+    ) -> Rgba32FImage {
+        blur_rgba_f32(img, sigma, border)
+    }
+
+    async fn blur_luma_f32(
+        &self,
+        img: &LumaA32FImage,
+        sigma: f32,
+        border: BorderMode,
+    ) -> LumaA32FImage {
+        blur_luma_f32(img, sigma, border)
+    }
+
+    async fn dog_on_luma_f32(
+        &self,
+        img: &LumaA32FImage,
+        builder: GaussianBuilder,
+    ) -> LumaA32FImage {
+        dog_on_luma_f32(img, &builder.build_kernel())
+    }
+
+    async fn gradient_luma_f32(&self, img: &LumaA32FImage, data: GradientData) -> GradientField {
+        gradient_luma_f32(img, data)
+    }
+
+    async fn luminance_mask(&self, img: &LumaAImage, data: LuminanceMask) -> LumaAImage {
+        luminance_mask(img, data)
+    }
+
+    async fn apply_mask(&self, img: &RgbaImage, mask: &LumaAImage, data: MaskData) -> RgbaImage {
+        apply_mask(img, mask, data)
+    }
+
+    async fn fit_rgba(
+        &self,
+        img: &RgbaImage,
+        width: u32,
+        height: u32,
+        fit: SizePolicy,
+    ) -> RgbaImage {
+        fit_rgba(img, width, height, fit)
+    }
+
+    async fn blend_rgba(&self, img: &RgbaImage, top: &RgbaImage, data: BlendData) -> RgbaImage {
+        blend_rgba(img, top, data)
+    }
+
+    async fn lut_rgba(&self, img: &RgbaImage, data: LutData) -> RgbaImage {
+        lut_on_rgba(img, &data)
+    }
+
+    async fn grade_rgba(&self, img: &RgbaImage, grade: ColourGrade) -> RgbaImage {
+        grade_on_rgba(img, grade)
+    }
+
+    async fn gradient_map(&self, img: &LumaAImage, map: GradientMap) -> RgbaImage {
+        gradient_map_on_luma(img, &map)
+    }
+
     async fn overlay_layers(&self, layers: &[LayerOutput]) -> Option<LayerOutput> {
         if layers.is_empty() {
             return None;
@@ -75,6 +413,12 @@ impl VisustaProcessor for VisustaCPU {
             return None;
         }
 
+        // Steps like the mosaic resize their layer, so layers can't be assumed to line up
+        let first_size = layers[0].dimensions();
+        if !layers.iter().all(|l| l.dimensions() == first_size) {
+            return None;
+        }
+
         match &layers[0] {
             LayerOutput::Rgba(_) => {
                 let rgba_layers: Vec<&RgbaImage> = layers
@@ -106,11 +450,79 @@ impl VisustaProcessor for VisustaCPU {
                     .collect();
                 Some(LayerOutput::Char(overlay_all_char(&char_layers)))
             }
+            LayerOutput::Gradient(_) => {
+                let gradient_layers: Vec<&GradientField> = layers
+                    .iter()
+                    .filter_map(|l| match l {
+                        LayerOutput::Gradient(field) => Some(field),
+                        _ => None,
+                    })
+                    .collect();
+                Some(LayerOutput::Gradient(overlay_all_gradient(
+                    &gradient_layers,
+                )))
+            }
+            LayerOutput::Rgba32F(first) => {
+                let float_layers: Vec<((u32, u32), &[f32])> = layers
+                    .iter()
+                    .filter_map(|l| match l {
+                        LayerOutput::Rgba32F(img) => {
+                            Some((img.dimensions(), img.as_raw().as_slice()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let buf = overlay_all_f32(&float_layers, 4);
+                Some(LayerOutput::Rgba32F(
+                    ImageBuffer::from_raw(first.width(), first.height(), buf)
+                        .expect("Overlay buffer should be sized correctly"),
+                ))
+            }
+            LayerOutput::Luma32F(first) => {
+                let float_layers: Vec<((u32, u32), &[f32])> = layers
+                    .iter()
+                    .filter_map(|l| match l {
+                        LayerOutput::Luma32F(img) => {
+                            Some((img.dimensions(), img.as_raw().as_slice()))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                let buf = overlay_all_f32(&float_layers, 2);
+                Some(LayerOutput::Luma32F(
+                    ImageBuffer::from_raw(first.width(), first.height(), buf)
+                        .expect("Overlay buffer should be sized correctly"),
+                ))
+            }
         }
     }
 }
 
-const MAX_SOBEL_SQ: f32 = 255.0 * 255.0;
+fn resolve_magnitude_min(
+    field: &GradientField,
+    magnitude: GradientMagnitude,
+    cutoff: Cutoff,
+) -> f32 {
+    match cutoff {
+        Cutoff::Value(val) => val,
+        Cutoff::Otsu => {
+            let mut hist = [0u32; 256];
+            for (gx, gy) in field.gx.iter().zip(field.gy.iter()) {
+                hist[magnitude.normal(*gx, *gy) as usize] += 1;
+            }
+
+            // Otsu's value is the top of the background class
+            otsu_threshold(&hist) as f32 + 1.0
+        }
+    }
+}
+
+fn resolve_cutoff(response: &Plane, cutoff: Option<Cutoff>) -> Option<f32> {
+    cutoff.map(|cutoff| match cutoff {
+        Cutoff::Value(val) => val,
+        Cutoff::Otsu => otsu_cutoff(&response.data),
+    })
+}
 
 pub fn rgb_luminance_u8(img: &RgbaImage, filter: LuminanceFilter) -> LumaAImage {
     let width = img.width() as usize;
@@ -156,13 +568,52 @@ pub fn rgb_luminance_u8(img: &RgbaImage, filter: LuminanceFilter) -> LumaAImage
         .expect("Luminance buffer should be sized correctly")
 }
 
-pub fn page_to_direction_colour(img: &LumaAImage, filter: SobelColorData) -> RgbaImage {
-    assert!(
-        !matches!(filter.a, SobelColorItem::None),
-        "Should not allow none on a channel sobel filter"
+pub fn page_to_direction_colour(
+    img: &LumaAImage,
+    filter: SobelColorData,
+    original: Option<&RgbaImage>,
+) -> RgbaImage {
+    // Every pixel gets a gradient, the border policy fills in the neighbours past the edge
+    let field = gradient_on_luma(
+        img,
+        GradientData::create()
+            .operator(filter.operator)
+            .border(filter.border),
     );
-    let width = img.width() as usize;
-    let height = img.height() as usize;
+
+    gradient_to_colour(&field, &GradientColourData::from(&filter), original)
+}
+
+// The original pixel for ChannelSource::Original, grey from luminance when there is no image
+fn original_pixel(
+    original: Option<&RgbaImage>,
+    x: usize,
+    y: usize,
+    luminance: f32,
+    alpha: f32,
+) -> [f32; 4] {
+    match original {
+        Some(img) => img.get_pixel(x as u32, y as u32).0.map(f32::from),
+        None => [luminance, luminance, luminance, alpha],
+    }
+}
+
+fn gradient_to_colour(
+    field: &GradientField,
+    filter: &GradientColourData,
+    original: Option<&RgbaImage>,
+) -> RgbaImage {
+    let width = field.width;
+    let height = field.height;
+
+    if let Some(original) = original {
+        assert!(
+            original.dimensions() == (width as u32, height as u32),
+            "Original and gradient field must have same dimensions"
+        );
+    }
+
+    let magnitude_min = resolve_magnitude_min(field, filter.magnitude, filter.magnitude_min);
 
     let mut sobel_buff = vec![0u8; width * height * 4];
 
@@ -170,65 +621,38 @@ pub fn page_to_direction_colour(img: &LumaAImage, filter: SobelColorData) -> Rgb
         .par_chunks_mut(width * 4)
         .enumerate()
         .for_each(|(y, row)| {
-            if y == 0 || y == height - 1 {
-                return;
-            }
-
-            for x in 1..(width - 1) {
-                let nw = img.get_pixel((x - 1) as u32, (y - 1) as u32).0[0] as i32;
-                let n = img.get_pixel(x as u32, (y - 1) as u32).0[0] as i32;
-                let ne = img.get_pixel((x + 1) as u32, (y - 1) as u32).0[0] as i32;
-
-                let w = img.get_pixel((x - 1) as u32, y as u32).0[0] as i32;
-                let e = img.get_pixel((x + 1) as u32, y as u32).0[0] as i32;
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let gx = field.gx(x, y);
+                let gy = field.gy(x, y);
 
-                let sw = img.get_pixel((x - 1) as u32, (y + 1) as u32).0[0] as i32;
-                let s = img.get_pixel(x as u32, (y + 1) as u32).0[0] as i32;
-                let se = img.get_pixel((x + 1) as u32, (y + 1) as u32).0[0] as i32;
+                let normal = filter.magnitude.normal(gx, gy) as f32;
 
-                let gx = (ne - nw) + 2 * (e - w) + (se - sw);
-                let gy = (sw + s * 2 + se) - (nw + n * 2 + ne);
-
-                let mag_sq = (gx * gx + gy * gy) as f32;
-                let normal = ((mag_sq / MAX_SOBEL_SQ) * 255.0).min(255.0) as u8;
-
-                if normal < filter.magnitude_min {
+                if normal < magnitude_min {
                     continue;
                 }
 
-                let out_idx = x * 4;
-
-                row[out_idx] = match filter.r {
-                    SobelColorItem::NormalScale(s) => ((normal as f32) * s) as u8,
-                    SobelColorItem::GxScale(s) => ((gx as f32) * s) as u8,
-                    SobelColorItem::GyScale(s) => ((gy as f32) * s) as u8,
-                    SobelColorItem::Absolute(v) => v,
-                    SobelColorItem::None => 0,
+                let idx = y * width + x;
+                let luminance = field.luma[idx];
+
+                let colour = match &filter.colouring {
+                    SobelColouring::Channels(channels) => channels.map(&ChannelSample {
+                        magnitude: normal,
+                        gx,
+                        gy,
+                        response: normal,
+                        luminance,
+                        original: original_pixel(
+                            original,
+                            x,
+                            y,
+                            luminance,
+                            field.alpha[idx] as f32,
+                        ),
+                    }),
+                    SobelColouring::DirectionHue(hue) => hue.map(gx, gy, normal),
                 };
 
-                row[out_idx + 1] = match filter.g {
-                    SobelColorItem::NormalScale(s) => ((normal as f32) * s) as u8,
-                    SobelColorItem::GxScale(s) => ((gx as f32) * s) as u8,
-                    SobelColorItem::GyScale(s) => ((gy as f32) * s) as u8,
-                    SobelColorItem::Absolute(v) => v,
-                    SobelColorItem::None => 0,
-                };
-
-                row[out_idx + 2] = match filter.b {
-                    SobelColorItem::NormalScale(s) => ((normal as f32) * s) as u8,
-                    SobelColorItem::GxScale(s) => ((gx as f32) * s) as u8,
-                    SobelColorItem::GyScale(s) => ((gy as f32) * s) as u8,
-                    SobelColorItem::Absolute(v) => v,
-                    SobelColorItem::None => 0,
-                };
-
-                row[out_idx + 3] = match filter.a {
-                    SobelColorItem::NormalScale(s) => ((normal as f32) * s) as u8,
-                    SobelColorItem::GxScale(s) => ((gx as f32) * s) as u8,
-                    SobelColorItem::GyScale(s) => ((gy as f32) * s) as u8,
-                    SobelColorItem::Absolute(v) => v,
-                    SobelColorItem::None => 0,
-                }
+                pixel.copy_from_slice(&colour);
             }
         });
 
@@ -237,47 +661,21 @@ pub fn page_to_direction_colour(img: &LumaAImage, filter: SobelColorData) -> Rgb
 }
 
 pub fn gaussian_on_luminance(img: &LumaAImage, kernel_data: GaussianKernelData) -> LumaAImage {
-    let width = img.width();
-    let height = img.height();
-
-    let kernel = kernel_data.kernel;
-
-    let mut gaussian_buff = vec![0u8; (width * height * 2) as usize];
-
-    gaussian_buff
-        .par_chunks_mut(width as usize * 2)
-        .enumerate()
-        .for_each(|(y, row)| {
-            let y = y as u32;
-            if y == 0 || y == height - 1 {
-                return;
+    let response = difference_of_gaussians(&Plane::from_luma(img), &kernel_data);
+    let cutoff = resolve_cutoff(&response, kernel_data.cutoff);
+
+    let gaussian_buff = response
+        .data
+        .iter()
+        .flat_map(|acc| {
+            // Just using this as a binary cutoff right now
+            if cutoff.is_some_and(|cutoff| *acc > cutoff) {
+                [255u8, 255u8]
+            } else {
+                [0u8, 0u8]
             }
-
-            for x in 1..(width - 1) {
-                let mut acc = 0f32;
-
-                acc += img.get_pixel(x - 1, y - 1).0[0] as f32 * kernel[0];
-                acc += img.get_pixel(x, y - 1).0[0] as f32 * kernel[1];
-                acc += img.get_pixel(x + 1, y - 1).0[0] as f32 * kernel[2];
-
-                acc += img.get_pixel(x - 1, y).0[0] as f32 * kernel[3];
-                acc += img.get_pixel(x, y).0[0] as f32 * kernel[4];
-                acc += img.get_pixel(x + 1, y).0[0] as f32 * kernel[5];
-
-                acc += img.get_pixel(x - 1, y + 1).0[0] as f32 * kernel[6];
-                acc += img.get_pixel(x, y + 1).0[0] as f32 * kernel[7];
-                acc += img.get_pixel(x + 1, y + 1).0[0] as f32 * kernel[8];
-
-                // Just using this as a binary cutoff right now
-                if kernel_data.cutoff.is_some_and(|cutoff| acc > cutoff) {
-                    row[(x * 2) as usize] = 255u8;
-                    row[(x * 2) as usize + 1] = 255u8;
-                    // continue;
-                }
-
-                // row[x] = min((acc * 12.0) as u32, 255u32) as u8;
-            }
-        });
+        })
+        .collect();
 
     ImageBuffer::from_raw(img.width(), img.height(), gaussian_buff)
         .expect("Luminance buffer should be sized correctly")
@@ -286,65 +684,42 @@ pub fn gaussian_on_luminance(img: &LumaAImage, kernel_data: GaussianKernelData)
 fn gaussian_to_coloured(
     img: &LumaAImage,
     kernel_data: GaussianKernelData,
-    filter: GaussianColorData,
+    channels: ChannelMapping,
+    original: Option<&RgbaImage>,
 ) -> RgbaImage {
     let width = img.width();
-    let height = img.height();
 
-    let kernel = kernel_data.kernel;
+    if let Some(original) = original {
+        assert!(
+            original.dimensions() == img.dimensions(),
+            "Original and image must have same dimensions"
+        );
+    }
+
+    let response = difference_of_gaussians(&Plane::from_luma(img), &kernel_data);
+    let cutoff = resolve_cutoff(&response, kernel_data.cutoff);
 
-    let mut gaussian_buff = vec![0u8; (width * height * 4) as usize];
+    let mut gaussian_buff = vec![0u8; (width * img.height() * 4) as usize];
 
     gaussian_buff
         .par_chunks_mut(width as usize * 4)
+        .zip(response.data.par_chunks(width as usize))
         .enumerate()
-        .for_each(|(y, row)| {
-            let y = y as u32;
-            if y == 0 || y == height - 1 {
-                return;
-            }
-
-            for x in 1..(width - 1) {
-                let mut acc = 0f32;
-
-                acc += img.get_pixel(x - 1, y - 1).0[0] as f32 * kernel[0];
-                acc += img.get_pixel(x, y - 1).0[0] as f32 * kernel[1];
-                acc += img.get_pixel(x + 1, y - 1).0[0] as f32 * kernel[2];
-
-                acc += img.get_pixel(x - 1, y).0[0] as f32 * kernel[3];
-                acc += img.get_pixel(x, y).0[0] as f32 * kernel[4];
-                acc += img.get_pixel(x + 1, y).0[0] as f32 * kernel[5];
-
-                acc += img.get_pixel(x - 1, y + 1).0[0] as f32 * kernel[6];
-                acc += img.get_pixel(x, y + 1).0[0] as f32 * kernel[7];
-                acc += img.get_pixel(x + 1, y + 1).0[0] as f32 * kernel[8];
-
-                if kernel_data.cutoff.is_some_and(|cutoff| acc > cutoff) {
-                    let out_idx = (x * 4) as usize;
-
-                    row[out_idx] = match filter.r {
-                        GaussianColorItem::NormalScale(scalar) => (acc * scalar) as u8,
-                        GaussianColorItem::Absolute(val) => val,
-                        GaussianColorItem::None => 0,
-                    };
-
-                    row[out_idx + 1] = match filter.g {
-                        GaussianColorItem::NormalScale(scalar) => (acc * scalar) as u8,
-                        GaussianColorItem::Absolute(val) => val,
-                        GaussianColorItem::None => 0,
-                    };
-
-                    row[out_idx + 2] = match filter.b {
-                        GaussianColorItem::NormalScale(scalar) => (acc * scalar) as u8,
-                        GaussianColorItem::Absolute(val) => val,
-                        GaussianColorItem::None => 0,
-                    };
-
-                    row[out_idx + 3] = match filter.a {
-                        GaussianColorItem::NormalScale(scalar) => (acc * scalar) as u8,
-                        GaussianColorItem::Absolute(val) => val,
-                        GaussianColorItem::None => 0,
-                    };
+        .for_each(|(y, (row, response_row))| {
+            for (x, (pixel, acc)) in row
+                .chunks_exact_mut(4)
+                .zip(response_row.iter().copied())
+                .enumerate()
+            {
+                if cutoff.is_some_and(|cutoff| acc > cutoff) {
+                    let [luminance, alpha] = img.get_pixel(x as u32, y as u32).0.map(f32::from);
+
+                    pixel.copy_from_slice(&channels.map(&ChannelSample {
+                        response: acc,
+                        luminance,
+                        original: original_pixel(original, x, y, luminance, alpha),
+                        ..ChannelSample::default()
+                    }));
                 }
             }
         });
@@ -418,8 +793,21 @@ fn luminance_to_ascii(img: &LumaAImage, filter: LuminanceAsciiFilter) -> CharIma
 }
 
 fn sobel_ascii_directional(img: &LumaAImage, filter: SobelAscii) -> CharImage {
-    let width = img.width();
-    let height = img.height() as usize;
+    let field = gradient_on_luma(
+        img,
+        GradientData::create()
+            .operator(filter.operator)
+            .border(filter.border),
+    );
+
+    gradient_to_ascii(&field, &GradientAsciiData::from(&filter))
+}
+
+fn gradient_to_ascii(field: &GradientField, filter: &GradientAsciiData) -> CharImage {
+    let width = field.width as u32;
+    let height = field.height;
+
+    let magnitude_min = resolve_magnitude_min(field, filter.magnitude, filter.magnitude_min);
 
     // We're creating the initial buff to know the direction
     //  at the pixel level.
@@ -430,30 +818,12 @@ fn sobel_ascii_directional(img: &LumaAImage, filter: SobelAscii) -> CharImage {
         .par_chunks_mut(width as usize)
         .enumerate()
         .for_each(|(y, row)| {
-            if y == 0 || y == height - 1 {
-                return;
-            }
-
-            for x in 1..(width - 1) {
-                let nw = img.get_pixel(x - 1, (y - 1) as u32).0[0] as i32;
-                let n = img.get_pixel(x, (y - 1) as u32).0[0] as i32;
-                let ne = img.get_pixel(x + 1, (y - 1) as u32).0[0] as i32;
-
-                let w = img.get_pixel(x - 1, y as u32).0[0] as i32;
-                let e = img.get_pixel(x + 1, y as u32).0[0] as i32;
-
-                let sw = img.get_pixel(x - 1, (y + 1) as u32).0[0] as i32;
-                let s = img.get_pixel(x, (y + 1) as u32).0[0] as i32;
-                let se = img.get_pixel(x + 1, (y + 1) as u32).0[0] as i32;
-
-                let gx = (ne - nw) + 2 * (e - w) + (se - sw);
-                let gy = (sw + s * 2 + se) - (nw + n * 2 + ne);
-
-                let mag_sq = (gx * gx + gy * gy) as f32;
-                let normal = ((mag_sq / MAX_SOBEL_SQ) * 255.0).min(255.0) as u8;
+            for (x, direction) in row.iter_mut().enumerate() {
+                let gx = field.gx(x, y);
+                let gy = field.gy(x, y);
 
-                if normal >= filter.magnitude_min {
-                    row[x as usize] = sobel_dir_gx_gy(gx, gy);
+                if filter.magnitude.normal(gx, gy) as f32 >= magnitude_min {
+                    *direction = sobel_dir_gx_gy(gx, gy);
                 }
             }
         });
@@ -557,8 +927,8 @@ fn sobel_ascii_directional(img: &LumaAImage, filter: SobelAscii) -> CharImage {
     }
 }
 
-pub fn sobel_dir_gx_gy(gx: i32, gy: i32) -> DirectionAscii {
-    let mut dir = atan2f(gy as f32, gx as f32);
+pub fn sobel_dir_gx_gy(gx: f32, gy: f32) -> DirectionAscii {
+    let mut dir = atan2f(gy, gx);
 
     if dir < 0.0 {
         dir += PI;
@@ -689,6 +1059,35 @@ fn overlay_all_luma(layers: &[&LumaAImage]) -> LumaAImage {
     LumaAImage::from_raw(width, height, out).unwrap()
 }
 
+// The strongest gradient wins at each pixel, tone and alpha come from the first layer
+fn overlay_all_gradient(layers: &[&GradientField]) -> GradientField {
+    assert!(!layers.is_empty(), "Must have at least one layer");
+
+    for layer in layers.iter().skip(1) {
+        assert!(
+            layer.width == layers[0].width && layer.height == layers[0].height,
+            "All layers must have same dimensions"
+        );
+    }
+
+    let mut out = layers[0].clone();
+
+    out.gx
+        .par_iter_mut()
+        .zip(out.gy.par_iter_mut())
+        .enumerate()
+        .for_each(|(idx, (gx, gy))| {
+            for layer in layers.iter().skip(1) {
+                if layer.gx[idx].hypot(layer.gy[idx]) > gx.hypot(*gy) {
+                    *gx = layer.gx[idx];
+                    *gy = layer.gy[idx];
+                }
+            }
+        });
+
+    out
+}
+
 fn overlay_all_char(layers: &[&CharImage]) -> CharImage {
     assert!(!layers.is_empty(), "Must have at least one layer");
 
@@ -721,3 +1120,695 @@ fn overlay_all_char(layers: &[&CharImage]) -> CharImage {
         data,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Waker};
+
+    use image::{ImageBuffer, LumaA};
+    use visusta_core::{
+        GradientAsciiData, GradientColourData, LumaAImage, LuminanceFilter, SobelAscii,
+        SobelColorData, SobelColouring, VisustaProcessor,
+        auxiliary::{AuxiliaryImage, BlendData, BlendMode, SizePolicy},
+        border::BorderMode,
+        channel::{ChannelMap, ChannelMapping, ChannelSource, RgbaChannel},
+        gradient::{GradientData, GradientField, GradientMagnitude, GradientOperator},
+        hatch::HatchData,
+        histogram::{ClaheData, HistogramFilter},
+        kuwahara::KuwaharaData,
+        lut::{CubeLut, LutData, LutInterpolation},
+        mask::{MaskData, MaskMode},
+        morphology::{MorphologyOp, StructuringElement, StructuringShape},
+        mosaic::{MosaicData, TileSet},
+        pipeline::{
+            DataType, Layer, LayerOutput, Pipeline, PipelineErrorKind, PipelineLocation,
+            ProcessingStep, StepInput,
+        },
+        threshold::Cutoff,
+    };
+
+    use super::VisustaCPU;
+    use super::overlay_all_gradient;
+    use super::{page_to_direction_colour, sobel_ascii_directional};
+    use crate::auxiliary::fit_rgba;
+    use crate::convolve::{Plane, gradients};
+    use crate::delaunay::triangulate;
+    use crate::float::overlay_all_f32;
+    use crate::gaussians::blur_luma;
+    use crate::grade::lut_on_rgba;
+    use crate::histogram::histogram_on_luma;
+    use crate::mask::apply_mask;
+    use crate::morphology::morphology_on_luma;
+    use crate::mosaic::mosaic_with_grid;
+    use crate::voronoi::jump_flood;
+
+    // The CPU processor never waits on anything, so a single poll runs a pipeline to the end
+    fn run<F: Future>(future: F) -> F::Output {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("CPU pipeline should not wait"),
+        }
+    }
+
+    // Dark on the left, bright from column `split` onwards
+    fn vertical_edge(width: u32, height: u32, split: u32) -> LumaAImage {
+        ImageBuffer::from_fn(width, height, |x, _| {
+            LumaA([if x < split { 0 } else { 255 }, 255])
+        })
+    }
+
+    fn sobel_colour(border: BorderMode) -> SobelColorData {
+        SobelColorData {
+            operator: GradientOperator::Sobel3,
+            magnitude: GradientMagnitude::Squared,
+            magnitude_min: Cutoff::Value(10.0),
+            border,
+            colouring: SobelColouring::Channels(ChannelMapping::solid(255, 255, 255, 255)),
+        }
+    }
+
+    #[test]
+    fn sobel_colour_fills_border_rows() {
+        let img = vertical_edge(6, 6, 3);
+        let out = page_to_direction_colour(&img, sobel_colour(BorderMode::Clamp), None);
+
+        for y in [0, 5] {
+            assert_eq!(out.get_pixel(2, y).0[3], 255, "edge missing on row {y}");
+            assert_eq!(out.get_pixel(3, y).0[3], 255, "edge missing on row {y}");
+            assert_eq!(out.get_pixel(0, y).0[3], 0, "false edge on row {y}");
+        }
+    }
+
+    #[test]
+    fn sobel_border_modes_read_past_the_edge() {
+        let img = vertical_edge(6, 6, 3);
+
+        let clamp = page_to_direction_colour(&img, sobel_colour(BorderMode::Clamp), None);
+        let reflect = page_to_direction_colour(&img, sobel_colour(BorderMode::Reflect), None);
+        let wrap = page_to_direction_colour(&img, sobel_colour(BorderMode::Wrap), None);
+
+        assert_eq!(clamp.get_pixel(0, 2).0[3], 0);
+        assert_eq!(reflect.get_pixel(0, 2).0[3], 0);
+        // Wrapping puts the bright right column next to the dark left one
+        assert_eq!(wrap.get_pixel(0, 2).0[3], 255);
+        assert_eq!(wrap.get_pixel(5, 2).0[3], 255);
+
+        let flat: LumaAImage = ImageBuffer::from_pixel(6, 6, LumaA([128, 255]));
+
+        let clamp = page_to_direction_colour(&flat, sobel_colour(BorderMode::Clamp), None);
+        let constant =
+            page_to_direction_colour(&flat, sobel_colour(BorderMode::Constant(0.0)), None);
+
+        assert!(clamp.pixels().all(|pixel| pixel.0[3] == 0));
+        assert_eq!(constant.get_pixel(0, 0).0[3], 255);
+        assert_eq!(constant.get_pixel(2, 2).0[3], 0);
+    }
+
+    #[test]
+    fn sobel_ascii_reads_the_border_row() {
+        let img = vertical_edge(4, 4, 2);
+        let filter = SobelAscii::create().font_size(2).magnitude_min(10);
+
+        let out = sobel_ascii_directional(&img, filter);
+
+        // Top left cell covers the edge on rows 0 and 1
+        assert_ne!(out.data[0], ' ');
+        assert_ne!(out.data[out.width], ' ');
+    }
+
+    #[test]
+    fn blur_keeps_flat_borders_unless_constant() {
+        let img: LumaAImage = ImageBuffer::from_pixel(7, 5, LumaA([200, 255]));
+
+        for border in [BorderMode::Clamp, BorderMode::Reflect, BorderMode::Wrap] {
+            let out = blur_luma(&img, 1.5, border);
+            assert!(
+                out.pixels().all(|pixel| pixel.0[0] == 200),
+                "{border:?} changed a flat image"
+            );
+        }
+
+        let out = blur_luma(&img, 1.5, BorderMode::Constant(0.0));
+        assert!(out.get_pixel(0, 0).0[0] < out.get_pixel(3, 2).0[0]);
+    }
+
+    #[test]
+    fn gradient_operators_share_sobel_units() {
+        // A ramp rising by 10 per pixel reads as 8 * 10 for the 3x3 Sobel
+        let ramp = Plane {
+            width: 9,
+            height: 9,
+            data: (0..81).map(|idx| (idx % 9) as f32 * 10.0).collect(),
+        };
+
+        for operator in [
+            GradientOperator::Sobel3,
+            GradientOperator::Sobel5,
+            GradientOperator::Scharr,
+            GradientOperator::Prewitt,
+            GradientOperator::Roberts,
+        ] {
+            let (gx, gy) = gradients(&ramp, operator, BorderMode::Clamp);
+            let idx = 4 * 9 + 4;
+
+            assert!((gx.data[idx] - 80.0).abs() < 1e-3, "{operator:?} gx");
+            assert!(gy.data[idx].abs() < 1e-3, "{operator:?} gy");
+        }
+    }
+
+    #[test]
+    fn linear_magnitude_scales_with_edge_strength() {
+        let linear = GradientMagnitude::Linear;
+
+        assert_eq!(linear.normal(4.0 * 255.0, 0.0), 255);
+        assert_eq!(linear.normal(4.0 * 100.0, 0.0), 100);
+        assert_eq!(linear.normal(0.0, 4.0 * 50.0), 50);
+
+        // The squared measure stays as it was
+        assert_eq!(GradientMagnitude::Squared.normal(255.0, 0.0), 255);
+        assert_eq!(GradientMagnitude::Squared.normal(128.0, 0.0), 64);
+    }
+
+    #[test]
+    fn mask_modes_split_the_image() {
+        let mask = vertical_edge(8, 2, 4);
+        let img = ImageBuffer::from_pixel(8, 2, image::Rgba([10, 20, 30, 255]));
+
+        let keep = apply_mask(&img, &mask, MaskData::create(MaskMode::Keep));
+        let remove = apply_mask(&img, &mask, MaskData::create(MaskMode::Remove));
+
+        assert_eq!(keep.get_pixel(1, 0).0, [10, 20, 30, 0]);
+        assert_eq!(keep.get_pixel(6, 0).0, [10, 20, 30, 255]);
+        assert_eq!(remove.get_pixel(1, 0).0[3], 255);
+        assert_eq!(remove.get_pixel(6, 0).0[3], 0);
+
+        // Feathering ramps alpha across the edge instead of cutting it
+        let feathered = apply_mask(&img, &mask, MaskData::create(MaskMode::Keep).feather(1.5));
+        let alpha: Vec<u8> = (0..8).map(|x| feathered.get_pixel(x, 0).0[3]).collect();
+
+        assert!(alpha.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(alpha[3] > 0 && alpha[3] < 128);
+        assert!(alpha[4] > 128 && alpha[4] < 255);
+    }
+
+    #[test]
+    fn identity_cube_leaves_colours_alone() {
+        let img = ImageBuffer::from_fn(16, 16, |x, y| {
+            image::Rgba([(x * 17) as u8, (y * 17) as u8, ((x + y) * 8) as u8, 200])
+        });
+
+        for interpolation in [LutInterpolation::Trilinear, LutInterpolation::Tetrahedral] {
+            let data = LutData::create(CubeLut::identity(9)).interpolation(interpolation);
+            assert_eq!(
+                lut_on_rgba(&img, &data),
+                img,
+                "{interpolation:?} moved a colour"
+            );
+        }
+    }
+
+    #[test]
+    fn clahe_ignores_tiles_past_the_image() {
+        // 10 pixels over 8 tiles rounds up to 2 pixel tiles, only 5 of them cover the image
+        let img: LumaAImage = ImageBuffer::from_pixel(10, 4, LumaA([100, 255]));
+        let filter = HistogramFilter::Clahe(ClaheData::create().tiles(8, 1));
+
+        let out = histogram_on_luma(&img, filter);
+        let first = out.get_pixel(0, 0).0;
+
+        assert!(out.pixels().all(|pixel| pixel.0 == first));
+    }
+
+    #[test]
+    fn mosaic_grid_names_the_composited_tiles() {
+        let tile = |val: u8| ImageBuffer::from_pixel(2, 2, image::Rgba([val, val, val, 255]));
+        let tiles = TileSet::from_images(vec![
+            ("black".to_string(), tile(0)),
+            ("white".to_string(), tile(255)),
+        ])
+        .unwrap();
+        let data = MosaicData::create(Arc::new(tiles), 2);
+
+        let img = ImageBuffer::from_fn(4, 2, |x, _| {
+            let val = if x < 2 { 10 } else { 240 };
+            image::Rgba([val, val, val, 255])
+        });
+
+        let (out, grid) = mosaic_with_grid(&img, &data);
+
+        assert_eq!((grid.width, grid.height), (2, 1));
+        assert_eq!(
+            grid.tiles,
+            vec![Some("black".to_string()), Some("white".to_string())]
+        );
+        assert_eq!(out.dimensions(), (4, 2));
+        assert_eq!(out.get_pixel(0, 0).0, [0, 0, 0, 255]);
+        assert_eq!(out.get_pixel(3, 1).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn overlay_rejects_a_resized_mosaic_layer() {
+        let tiles = TileSet::from_images(vec![(
+            "grey".to_string(),
+            ImageBuffer::from_pixel(4, 4, image::Rgba([128, 128, 128, 255])),
+        )])
+        .unwrap();
+        let img = ImageBuffer::from_pixel(4, 4, image::Rgba([200, 200, 200, 255]));
+
+        // Blocks of 2 become 4x4 tiles, so the mosaic layer is 8x8 over a 4x4 source layer
+        let pipeline = Pipeline::new()
+            .add_layer(
+                Layer::new().add_step(ProcessingStep::Mosaic(MosaicData::create(
+                    Arc::new(tiles),
+                    2,
+                ))),
+            )
+            .add_layer(Layer::new().add_step(ProcessingStep::Kuwahara(KuwaharaData::create(1))));
+
+        let Ok(outputs) = run(pipeline.execute(&img, &VisustaCPU)) else {
+            panic!("Both layers should run");
+        };
+
+        assert!(run(VisustaCPU.overlay_layers(&outputs)).is_none());
+    }
+
+    #[test]
+    fn morphology_gradient_keeps_alpha() {
+        let img = vertical_edge(8, 4, 4);
+        let element = StructuringElement::create(StructuringShape::Square, 1);
+
+        let out = morphology_on_luma(&img, MorphologyOp::Gradient(element));
+
+        assert!(out.pixels().all(|pixel| pixel.0[1] == 255));
+        assert_eq!(out.get_pixel(0, 0).0[0], 0);
+        assert_eq!(out.get_pixel(3, 0).0[0], 255);
+        assert_eq!(out.get_pixel(4, 0).0[0], 255);
+        assert_eq!(out.get_pixel(7, 0).0[0], 0);
+    }
+
+    #[test]
+    fn morphology_reads_past_the_edge_through_the_border() {
+        let img: LumaAImage = ImageBuffer::from_pixel(4, 4, LumaA([255, 255]));
+        let element = StructuringElement::create(StructuringShape::Square, 1);
+
+        let clamped = morphology_on_luma(&img, MorphologyOp::Erode(element));
+        let padded = morphology_on_luma(
+            &img,
+            MorphologyOp::Erode(element.border(BorderMode::Constant(0.0))),
+        );
+
+        assert!(clamped.pixels().all(|pixel| pixel.0 == [255, 255]));
+        assert_eq!(padded.get_pixel(0, 0).0, [0, 255]);
+        assert_eq!(padded.get_pixel(1, 1).0, [255, 255]);
+    }
+
+    #[test]
+    fn gradient_steps_validate_against_gradient_fields() {
+        let edges = || {
+            Layer::new()
+                .add_step(ProcessingStep::ToLuminance(LuminanceFilter::create()))
+                .add_step(ProcessingStep::Gradient(GradientData::create()))
+        };
+
+        let ascii = Pipeline::new().add_layer(
+            edges().add_step(ProcessingStep::GradientToAscii(GradientAsciiData::create())),
+        );
+        assert_eq!(ascii.validate().unwrap(), DataType::Char);
+
+        let colour = Pipeline::new().add_layer(edges().add_step(ProcessingStep::GradientToColour(
+            GradientColourData::create(SobelColouring::Channels(ChannelMapping::solid(
+                255, 255, 255, 255,
+            ))),
+            None,
+        )));
+        assert_eq!(colour.validate().unwrap(), DataType::Rgba);
+
+        // Reading a field straight from luminance, without the Gradient step between
+        let missing = Pipeline::new().add_layer(
+            Layer::new()
+                .add_step(ProcessingStep::ToLuminance(LuminanceFilter::create()))
+                .add_step(ProcessingStep::GradientToLuma(GradientMagnitude::Linear)),
+        );
+        let err = missing.validate().unwrap_err();
+        assert_eq!(
+            err.kind,
+            PipelineErrorKind::TypeMismatch {
+                expected: DataType::LumaA,
+                got: DataType::Gradient,
+            }
+        );
+        assert_eq!(err.location, PipelineLocation::Step { layer: 0, step: 1 });
+    }
+
+    #[test]
+    fn into_gradient_rejects_other_outputs() {
+        let output = LayerOutput::LumaA(vertical_edge(4, 4, 2));
+
+        assert_eq!(
+            output.into_gradient().unwrap_err(),
+            PipelineErrorKind::ExecutionMismatch {
+                expected: DataType::Gradient,
+                got: DataType::LumaA,
+            }
+        );
+    }
+
+    #[test]
+    fn overlay_all_gradient_keeps_the_strongest_gradient() {
+        let field = |gx: Vec<f32>, gy: Vec<f32>| GradientField {
+            width: 2,
+            height: 1,
+            gx,
+            gy,
+            luma: vec![0.0; 2],
+            alpha: vec![255; 2],
+            border: BorderMode::Clamp,
+        };
+
+        let first = field(vec![10.0, 0.0], vec![0.0, 3.0]);
+        let second = field(vec![0.0, -4.0], vec![5.0, 0.0]);
+
+        let out = overlay_all_gradient(&[&first, &second]);
+
+        assert_eq!(out.gx, vec![10.0, -4.0]);
+        assert_eq!(out.gy, vec![0.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "All layers must have same dimensions")]
+    fn overlay_all_f32_compares_sizes_not_lengths() {
+        let wide = vec![0.0f32; 2 * 8 * 2];
+        let square = vec![0.0f32; 4 * 4 * 2];
+
+        overlay_all_f32(&[((2, 8), &wide), ((4, 4), &square)], 2);
+    }
+
+    #[test]
+    fn colour_steps_read_original_channels_from_a_step_input() {
+        let img = ImageBuffer::from_fn(6, 4, |x, _| {
+            if x < 3 {
+                image::Rgba([200, 40, 10, 255])
+            } else {
+                image::Rgba([10, 60, 220, 255])
+            }
+        });
+
+        let colour = |original: Option<StepInput>| {
+            let layer = Layer::new()
+                .add_step(ProcessingStep::ToLuminance(LuminanceFilter::create()))
+                .add_step(ProcessingStep::SobelToColour(
+                    SobelColorData {
+                        operator: GradientOperator::Sobel3,
+                        magnitude: GradientMagnitude::Squared,
+                        magnitude_min: Cutoff::Value(0.0),
+                        border: BorderMode::Clamp,
+                        colouring: SobelColouring::Channels(ChannelMapping {
+                            r: ChannelMap::create(ChannelSource::Original(RgbaChannel::Red)),
+                            g: ChannelMap::create(ChannelSource::Original(RgbaChannel::Green)),
+                            b: ChannelMap::create(ChannelSource::Original(RgbaChannel::Blue)),
+                            a: ChannelMap::constant(255),
+                        }),
+                    },
+                    original,
+                ));
+
+            let outputs = run(Pipeline::new().add_layer(layer).execute(&img, &VisustaCPU)).unwrap();
+            outputs[0].as_rgba().unwrap().clone()
+        };
+
+        let original = colour(Some(StepInput::Source));
+        let grey = colour(None);
+
+        assert_eq!(original.as_raw(), img.as_raw());
+
+        let [r, g, b, _] = grey.get_pixel(0, 0).0;
+        assert!(r == g && g == b);
+    }
+
+    #[test]
+    fn apply_mask_reports_a_resized_target_layer() {
+        let tiles = TileSet::from_images(vec![(
+            "grey".to_string(),
+            ImageBuffer::from_pixel(4, 4, image::Rgba([128, 128, 128, 255])),
+        )])
+        .unwrap();
+        let img = ImageBuffer::from_pixel(4, 4, image::Rgba([200, 200, 200, 255]));
+
+        // Blocks of 2 become 4x4 tiles, so the first layer comes out 8x8
+        let pipeline = Pipeline::new()
+            .add_layer(
+                Layer::new().add_step(ProcessingStep::Mosaic(MosaicData::create(
+                    Arc::new(tiles),
+                    2,
+                ))),
+            )
+            .add_layer(
+                Layer::new()
+                    .add_step(ProcessingStep::ToLuminance(LuminanceFilter::create()))
+                    .add_step(ProcessingStep::ApplyMask(
+                        MaskData::create(MaskMode::Keep),
+                        StepInput::Layer(0),
+                    )),
+            );
+
+        let Err(err) = run(pipeline.execute(&img, &VisustaCPU)) else {
+            panic!("A mask over a resized layer should fail");
+        };
+
+        assert_eq!(
+            err.kind,
+            PipelineErrorKind::SizeMismatch {
+                expected: (4, 4),
+                got: (8, 8),
+            }
+        );
+        assert_eq!(err.location, PipelineLocation::Step { layer: 1, step: 1 });
+    }
+
+    #[test]
+    fn size_policies_fit_auxiliary_images() {
+        // 2x1, red then blue
+        let img = ImageBuffer::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgba([255, 0, 0, 255])
+            } else {
+                image::Rgba([0, 0, 255, 255])
+            }
+        });
+
+        let stretched = fit_rgba(&img, 4, 2, SizePolicy::Stretch);
+        assert_eq!(stretched.dimensions(), (4, 2));
+        assert_eq!(stretched.get_pixel(0, 1).0, [255, 0, 0, 255]);
+        assert_eq!(stretched.get_pixel(3, 0).0, [0, 0, 255, 255]);
+
+        let tiled = fit_rgba(&img, 5, 2, SizePolicy::Tile);
+        assert_eq!(tiled.get_pixel(2, 1).0, [255, 0, 0, 255]);
+        assert_eq!(tiled.get_pixel(3, 0).0, [0, 0, 255, 255]);
+
+        let centred = fit_rgba(&img, 4, 3, SizePolicy::Centre);
+        assert_eq!(centred.get_pixel(0, 1).0, [0, 0, 0, 0]);
+        assert_eq!(centred.get_pixel(1, 1).0, [255, 0, 0, 255]);
+        assert_eq!(centred.get_pixel(2, 1).0, [0, 0, 255, 255]);
+        assert_eq!(centred.get_pixel(1, 0).0, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn auxiliary_inputs_fit_the_step_that_reads_them() {
+        let tiles = TileSet::from_images(vec![(
+            "grey".to_string(),
+            ImageBuffer::from_pixel(4, 4, image::Rgba([128, 128, 128, 255])),
+        )])
+        .unwrap();
+        let img = ImageBuffer::from_pixel(4, 4, image::Rgba([200, 200, 200, 255]));
+        let paper = AuxiliaryImage::create(ImageBuffer::from_pixel(
+            3,
+            3,
+            image::Rgba([255, 255, 255, 255]),
+        ));
+
+        // The mosaic makes the layer 8x8, past the 4x4 source
+        let blended = |top: StepInput| {
+            Pipeline::new().add_input("paper", paper.clone()).add_layer(
+                Layer::new()
+                    .add_step(ProcessingStep::Mosaic(MosaicData::create(
+                        Arc::new(tiles.clone()),
+                        2,
+                    )))
+                    .add_step(ProcessingStep::Blend(
+                        BlendData::create(BlendMode::Multiply),
+                        top,
+                    )),
+            )
+        };
+
+        let Ok(outputs) =
+            run(blended(StepInput::Auxiliary("paper".to_string())).execute(&img, &VisustaCPU))
+        else {
+            panic!("An auxiliary input should be fitted to the resized layer");
+        };
+        let out = outputs[0].as_rgba().unwrap();
+        assert_eq!(out.dimensions(), (8, 8));
+        assert_eq!(out.get_pixel(7, 7).0, [128, 128, 128, 255]);
+
+        let Err(err) = run(blended(StepInput::Source).execute(&img, &VisustaCPU)) else {
+            panic!("The source is not fitted, so blending it over the mosaic should fail");
+        };
+        assert_eq!(
+            err.kind,
+            PipelineErrorKind::SizeMismatch {
+                expected: (8, 8),
+                got: (4, 4),
+            }
+        );
+        assert_eq!(err.location, PipelineLocation::Step { layer: 0, step: 1 });
+    }
+
+    #[test]
+    fn step_inputs_must_exist_before_the_step() {
+        let blend = |top: StepInput| {
+            Layer::new().add_step(ProcessingStep::Blend(
+                BlendData::create(BlendMode::Normal),
+                top,
+            ))
+        };
+
+        let unknown = Pipeline::new().add_layer(blend(StepInput::Auxiliary("paper".to_string())));
+        let err = unknown.validate().unwrap_err();
+        assert_eq!(
+            err.kind,
+            PipelineErrorKind::UnknownInput {
+                name: "paper".to_string(),
+            }
+        );
+        assert_eq!(err.location, PipelineLocation::Step { layer: 0, step: 0 });
+
+        // A layer can only read the layers before it
+        let forward = Pipeline::new()
+            .add_layer(blend(StepInput::Layer(1)))
+            .add_layer(blend(StepInput::Source));
+        let err = forward.validate().unwrap_err();
+        assert_eq!(err.kind, PipelineErrorKind::LayerReference { layer: 1 });
+        assert_eq!(err.location, PipelineLocation::Step { layer: 0, step: 0 });
+
+        let backward = Pipeline::new()
+            .add_layer(blend(StepInput::Source))
+            .add_layer(blend(StepInput::Layer(0)));
+        assert_eq!(backward.validate().unwrap(), DataType::Rgba);
+    }
+
+    #[test]
+    fn delaunay_triangles_have_empty_circumcircles() {
+        let points = [
+            (0.0, 0.0),
+            (8.0, 0.0),
+            (0.0, 6.0),
+            (8.0, 6.0),
+            (3.0, 2.0),
+            (5.5, 4.0),
+            (1.5, 4.5),
+            (6.0, 1.0),
+        ];
+
+        let triangles = triangulate(&points);
+        assert!(!triangles.is_empty());
+
+        for [a, b, c] in triangles
+            .iter()
+            .map(|triangle| triangle.map(|idx| points[idx]))
+        {
+            let d = 2.0 * (a.0 * (b.1 - c.1) + b.0 * (c.1 - a.1) + c.0 * (a.1 - b.1));
+            assert!(d.abs() > 1e-3, "Triangle should not be degenerate");
+
+            let a_sq = a.0 * a.0 + a.1 * a.1;
+            let b_sq = b.0 * b.0 + b.1 * b.1;
+            let c_sq = c.0 * c.0 + c.1 * c.1;
+            let ux = (a_sq * (b.1 - c.1) + b_sq * (c.1 - a.1) + c_sq * (a.1 - b.1)) / d;
+            let uy = (a_sq * (c.0 - b.0) + b_sq * (a.0 - c.0) + c_sq * (b.0 - a.0)) / d;
+            let radius_sq = (a.0 - ux).powi(2) + (a.1 - uy).powi(2);
+
+            for point in points {
+                let dist_sq = (point.0 - ux).powi(2) + (point.1 - uy).powi(2);
+                assert!(
+                    dist_sq >= radius_sq - 1e-3,
+                    "{point:?} is inside a circumcircle"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn delaunay_skips_collinear_triangles() {
+        let line = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0)];
+        assert!(triangulate(&line).is_empty());
+
+        // One point off the line, every triangle has to fan out to it
+        let mut points = line.to_vec();
+        points.push((1.5, 2.0));
+
+        let triangles = triangulate(&points);
+        assert_eq!(triangles.len(), 3);
+
+        for [a, b, c] in triangles
+            .iter()
+            .map(|triangle| triangle.map(|idx| points[idx]))
+        {
+            let area = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+            assert!(area.abs() > 1e-3, "Triangle should not be degenerate");
+        }
+    }
+
+    #[test]
+    fn jump_flood_matches_the_nearest_seed() {
+        let (width, height) = (9, 7);
+        let seeds = [(1.5, 1.5), (7.2, 0.8), (4.5, 5.5), (0.3, 6.1), (8.5, 6.5)];
+
+        let labels = jump_flood(width, height, &seeds);
+
+        let dist_sq = |x: usize, y: usize, (sx, sy): (f32, f32)| {
+            (x as f32 + 0.5 - sx).powi(2) + (y as f32 + 0.5 - sy).powi(2)
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                let nearest = seeds
+                    .iter()
+                    .map(|seed| dist_sq(x, y, *seed))
+                    .fold(f32::INFINITY, f32::min);
+
+                // Compared by distance so ties between seeds can go either way
+                let label = labels[y * width + x] as usize;
+                assert_eq!(dist_sq(x, y, seeds[label]), nearest, "Pixel {x}, {y}");
+            }
+        }
+    }
+
+    #[test]
+    fn hatch_paper_follows_its_size_policy() {
+        // White has no strokes, so the output is the paper's luminance
+        let img = ImageBuffer::from_pixel(4, 2, image::Rgba([255, 255, 255, 255]));
+        let paper = AuxiliaryImage::create(ImageBuffer::from_fn(2, 1, |x, _| {
+            let val = if x == 0 { 0 } else { 255 };
+            image::Rgba([val, val, val, 255])
+        }))
+        .fit(SizePolicy::Tile);
+
+        let pipeline = Pipeline::new()
+            .add_input("paper", paper)
+            .add_layer(Layer::new().add_step(ProcessingStep::HatchRgba(
+                HatchData::create(),
+                Some(StepInput::Auxiliary("paper".to_string())),
+            )));
+
+        let Ok(outputs) = run(pipeline.execute(&img, &VisustaCPU)) else {
+            panic!("The paper should be tiled over the image");
+        };
+        let Some(LayerOutput::LumaA(out)) = outputs.first() else {
+            panic!("Hatching should output luminance");
+        };
+
+        let row: Vec<u8> = (0..4).map(|x| out.get_pixel(x, 1).0[0]).collect();
+        assert_eq!(row, vec![0, 255, 0, 255]);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+
+use image::RgbaImage;
+use rayon::prelude::*;
+use visusta_core::{
+    gradient::GradientOperator,
+    lowpoly::{LowPolyData, TriangleFill, TriangleMesh},
+    random::SplitMix64,
+};
+
+use crate::{
+    convolve::{Plane, gradients},
+    delaunay::triangulate,
+};
+
+// Points along each image edge on top of the corners, keeps border triangles from slivering
+const BORDER_POINTS: u32 = 8;
+
+pub fn low_poly_mesh(img: &RgbaImage, data: LowPolyData) -> TriangleMesh {
+    build_mesh(img, data).0
+}
+
+pub fn low_poly_on_rgba(img: &RgbaImage, data: LowPolyData) -> RgbaImage {
+    let (mesh, owners) = build_mesh(img, data);
+
+    let width = img.width() as usize;
+
+    let mut buf = vec![0u8; width * img.height() as usize * 4];
+
+    buf.par_chunks_mut(width * 4)
+        .zip(owners.par_chunks(width))
+        .for_each(|(row, owner_row)| {
+            for (pixel, owner) in row.chunks_exact_mut(4).zip(owner_row) {
+                if let Some(triangle) = owner {
+                    pixel.copy_from_slice(&mesh.colours[*triangle]);
+                }
+            }
+        });
+
+    RgbaImage::from_raw(img.width(), img.height(), buf)
+        .expect("Low poly buffer should be sized correctly")
+}
+
+// The mesh along with the triangle owning each pixel, so filling it needs no second raster pass
+fn build_mesh(img: &RgbaImage, data: LowPolyData) -> (TriangleMesh, Vec<Option<usize>>) {
+    let width = img.width();
+    let height = img.height();
+
+    let points = sample_points(img, data);
+    let triangles = triangulate(&points);
+
+    let owners = rasterize_owners(&points, &triangles, width, height);
+
+    let colours = match data.fill {
+        TriangleFill::Mean => mean_colours(img, &owners, triangles.len()),
+        TriangleFill::Centroid => triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|idx| points[idx]);
+                let cx = ((a.0 + b.0 + c.0) / 3.0) as u32;
+                let cy = ((a.1 + b.1 + c.1) / 3.0) as u32;
+                img.get_pixel(cx.min(width - 1), cy.min(height - 1)).0
+            })
+            .collect(),
+    };
+
+    let mesh = TriangleMesh {
+        width,
+        height,
+        points,
+        triangles,
+        colours,
+    };
+
+    (mesh, owners)
+}
+
+fn sample_points(img: &RgbaImage, data: LowPolyData) -> Vec<(f32, f32)> {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let (gx, gy) = gradients(
+        &Plane::from_rgba_luminance(img),
+        GradientOperator::Sobel3,
+        data.border,
+    );
+
+    let magnitudes: Vec<f32> = gx
+        .data
+        .iter()
+        .zip(gy.data.iter())
+        .map(|(gx, gy)| (gx * gx + gy * gy).sqrt())
+        .collect();
+
+    let max_magnitude = magnitudes.iter().copied().fold(0f32, f32::max).max(1.0);
+
+    // Running sum of the per pixel weight, a uniform draw is searched into it
+    let mut cumulative = Vec::with_capacity(magnitudes.len());
+    let mut total = 0f32;
+    for magnitude in magnitudes {
+        total += (1.0 - data.edge_bias) + data.edge_bias * magnitude / max_magnitude;
+        cumulative.push(total);
+    }
+
+    let (w, h) = (width as f32, height as f32);
+
+    let mut points = vec![(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)];
+    for step in 1..BORDER_POINTS {
+        let t = step as f32 / BORDER_POINTS as f32;
+        points.extend([(t * w, 0.0), (t * w, h), (0.0, t * h), (w, t * h)]);
+    }
+
+    let mut rng = SplitMix64::new(data.seed);
+    let mut taken = HashSet::new();
+
+    // Bounded so tiny images with few pixels can't loop forever on duplicates
+    let attempts = data.point_count as usize * 4;
+
+    for _ in 0..attempts {
+        if taken.len() >= data.point_count as usize {
+            break;
+        }
+
+        let target = rng.next_f32() * total;
+        let idx = cumulative
+            .partition_point(|val| *val < target)
+            .min(cumulative.len() - 1);
+
+        if taken.insert(idx) {
+            points.push(((idx % width) as f32 + 0.5, (idx / width) as f32 + 0.5));
+        }
+    }
+
+    points
+}
+
+// Triangle index covering each pixel centre
+fn rasterize_owners(
+    points: &[(f32, f32)],
+    triangles: &[[usize; 3]],
+    width: u32,
+    height: u32,
+) -> Vec<Option<usize>> {
+    let (width, height) = (width as usize, height as usize);
+    let mut owners = vec![None; width * height];
+
+    for (triangle_idx, triangle) in triangles.iter().enumerate() {
+        let [a, b, c] = triangle.map(|idx| points[idx]);
+
+        let edge = |p: (f32, f32), q: (f32, f32), x: f32, y: f32| {
+            (q.0 - p.0) * (y - p.1) - (q.1 - p.1) * (x - p.0)
+        };
+
+        let area = edge(a, b, c.0, c.1);
+        if area == 0.0 {
+            continue;
+        }
+
+        let min_x = (a.0.min(b.0).min(c.0).floor().max(0.0) as usize).min(width);
+        let max_x = (a.0.max(b.0).max(c.0).ceil().max(0.0) as usize).min(width);
+        let min_y = (a.1.min(b.1).min(c.1).floor().max(0.0) as usize).min(height);
+        let max_y = (a.1.max(b.1).max(c.1).ceil().max(0.0) as usize).min(height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+
+                // Same sign as the area on all three edges means inside, edges included
+                let w0 = edge(b, c, px, py) * area.signum();
+                let w1 = edge(c, a, px, py) * area.signum();
+                let w2 = edge(a, b, px, py) * area.signum();
+
+                if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
+                    owners[y * width + x] = Some(triangle_idx);
+                }
+            }
+        }
+    }
+
+    owners
+}
+
+fn mean_colours(img: &RgbaImage, owners: &[Option<usize>], count: usize) -> Vec<[u8; 4]> {
+    let mut sums = vec![[0u64; 5]; count];
+
+    for (pixel, owner) in img.pixels().zip(owners) {
+        if let Some(triangle) = owner {
+            let sum = &mut sums[*triangle];
+            for (acc, val) in sum.iter_mut().zip(pixel.0) {
+                *acc += val as u64;
+            }
+            sum[4] += 1;
+        }
+    }
+
+    sums.into_iter()
+        .map(|sum| {
+            if sum[4] == 0 {
+                return [0, 0, 0, 0];
+            }
+            std::array::from_fn(|channel| (sum[channel] / sum[4]) as u8)
+        })
+        .collect()
+}
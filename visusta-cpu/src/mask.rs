@@ -0,0 +1,73 @@
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::{
+    LumaAImage,
+    border::BorderMode,
+    gaussians::gaussian_kernel_1d,
+    mask::{LuminanceMask, MaskData, MaskMode},
+};
+
+use crate::convolve::{Plane, separable_blur};
+
+// Transparent pixels are never part of the mask, before or after inverting
+pub fn luminance_mask(img: &LumaAImage, data: LuminanceMask) -> LumaAImage {
+    let buf = img
+        .pixels()
+        .flat_map(|pixel| {
+            let [luminance, alpha] = pixel.0;
+            if alpha == 0 {
+                return [0, 255];
+            }
+
+            let coverage = data.coverage(luminance) * alpha as f32 / 255.0;
+            [(coverage * 255.0).round() as u8, 255]
+        })
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Mask buffer should be sized correctly")
+}
+
+// Coverage is luminance times alpha, so both [0, 0] and [0, 255] read as unset
+pub fn apply_mask(img: &RgbaImage, mask: &LumaAImage, data: MaskData) -> RgbaImage {
+    assert!(
+        img.dimensions() == mask.dimensions(),
+        "Mask and image must have same dimensions"
+    );
+
+    let mut coverage = Plane {
+        width: mask.width() as usize,
+        height: mask.height() as usize,
+        data: mask
+            .pixels()
+            .map(|pixel| pixel.0[0] as f32 * pixel.0[1] as f32 / (255.0 * 255.0))
+            .collect(),
+    };
+
+    if data.feather > 0.0 {
+        coverage = separable_blur(
+            &coverage,
+            &gaussian_kernel_1d(data.feather),
+            // Coverage is 0 - 1, the constant is a luminance like everywhere else
+            match data.border {
+                BorderMode::Constant(val) => BorderMode::Constant(val / 255.0),
+                border => border,
+            },
+        );
+    }
+
+    let mut out = img.clone();
+
+    out.par_chunks_mut(4)
+        .zip(coverage.data.par_iter())
+        .for_each(|(pixel, val)| {
+            let keep = match data.mode {
+                MaskMode::Keep => val.clamp(0.0, 1.0),
+                MaskMode::Remove => 1.0 - val.clamp(0.0, 1.0),
+            };
+
+            pixel[3] = (pixel[3] as f32 * keep).round() as u8;
+        });
+
+    out
+}
@@ -0,0 +1,173 @@
+use image::ImageBuffer;
+use rayon::prelude::*;
+use visusta_core::{
+    LumaAImage,
+    morphology::{MorphologyOp, StructuringElement},
+};
+
+// Both channels go through the operation so binary maps keep luma and alpha in step
+pub fn morphology_on_luma(img: &LumaAImage, op: MorphologyOp) -> LumaAImage {
+    match op {
+        MorphologyOp::Dilate(element) => rank_filter(img, element, Rank::Max),
+        MorphologyOp::Erode(element) => rank_filter(img, element, Rank::Min),
+        MorphologyOp::Open(element) => {
+            let eroded = rank_filter(img, element, Rank::Min);
+            rank_filter(&eroded, element, Rank::Max)
+        }
+        MorphologyOp::Close(element) => {
+            let dilated = rank_filter(img, element, Rank::Max);
+            rank_filter(&dilated, element, Rank::Min)
+        }
+        MorphologyOp::Gradient(element) => {
+            let dilated = rank_filter(img, element, Rank::Max);
+            let eroded = rank_filter(img, element, Rank::Min);
+
+            // Only luma is an outline, alpha keeps the dilated coverage so opaque input stays opaque
+            let buf = dilated
+                .pixels()
+                .zip(eroded.pixels())
+                .flat_map(|(dilated, eroded)| [dilated.0[0] - eroded.0[0], dilated.0[1]])
+                .collect();
+
+            ImageBuffer::from_raw(img.width(), img.height(), buf)
+                .expect("Morphology buffer should be sized correctly")
+        }
+        MorphologyOp::Skeletonize => skeletonize(img),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Rank {
+    Min,
+    Max,
+}
+
+// Out of bounds neighbours follow the element's border, a constant reads as opaque luminance
+fn rank_filter(img: &LumaAImage, element: StructuringElement, rank: Rank) -> LumaAImage {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+    let offsets = element.offsets();
+    let src = img.as_raw();
+    let constant = [
+        element.border.constant().round().clamp(0.0, 255.0) as u8,
+        255,
+    ];
+
+    let mut buf = vec![0u8; width * height * 2];
+
+    buf.par_chunks_mut(width * 2)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(2).enumerate() {
+                let mut acc = match rank {
+                    Rank::Min => [u8::MAX; 2],
+                    Rank::Max => [u8::MIN; 2],
+                };
+
+                for (dx, dy) in offsets.iter() {
+                    let nx = element.border.index(x as isize + *dx as isize, width);
+                    let ny = element.border.index(y as isize + *dy as isize, height);
+
+                    let val = match (nx, ny) {
+                        (Some(nx), Some(ny)) => {
+                            let idx = (ny * width + nx) * 2;
+                            &src[idx..idx + 2]
+                        }
+                        _ => &constant[..],
+                    };
+
+                    for (acc, val) in acc.iter_mut().zip(val) {
+                        *acc = match rank {
+                            Rank::Min => (*acc).min(*val),
+                            Rank::Max => (*acc).max(*val),
+                        };
+                    }
+                }
+
+                pixel.copy_from_slice(&acc);
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Morphology buffer should be sized correctly")
+}
+
+// Input is binarised at mid grey, the skeleton is written as 255,255 on 0,0
+fn skeletonize(img: &LumaAImage) -> LumaAImage {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let mut on: Vec<bool> = img
+        .pixels()
+        .map(|pixel| pixel.0[0] >= 128 && pixel.0[1] > 0)
+        .collect();
+
+    let at = |on: &[bool], x: isize, y: isize| {
+        x >= 0
+            && y >= 0
+            && (x as usize) < width
+            && (y as usize) < height
+            && on[y as usize * width + x as usize]
+    };
+
+    loop {
+        let mut changed = false;
+
+        for pass in 0..2 {
+            let removals: Vec<usize> = (0..width * height)
+                .into_par_iter()
+                .filter(|idx| {
+                    if !on[*idx] {
+                        return false;
+                    }
+
+                    let (x, y) = ((idx % width) as isize, (idx / width) as isize);
+
+                    // P2 to P9, clockwise from north
+                    let ring = [
+                        at(&on, x, y - 1),
+                        at(&on, x + 1, y - 1),
+                        at(&on, x + 1, y),
+                        at(&on, x + 1, y + 1),
+                        at(&on, x, y + 1),
+                        at(&on, x - 1, y + 1),
+                        at(&on, x - 1, y),
+                        at(&on, x - 1, y - 1),
+                    ];
+
+                    let neighbours = ring.iter().filter(|val| **val).count();
+                    let transitions = (0..8)
+                        .filter(|idx| !ring[*idx] && ring[(idx + 1) % 8])
+                        .count();
+
+                    let [p2, _, p4, _, p6, _, p8, _] = ring;
+
+                    let (first, second) = if pass == 0 {
+                        (p2 && p4 && p6, p4 && p6 && p8)
+                    } else {
+                        (p2 && p4 && p8, p2 && p6 && p8)
+                    };
+
+                    (2..=6).contains(&neighbours) && transitions == 1 && !first && !second
+                })
+                .collect();
+
+            changed |= !removals.is_empty();
+            for idx in removals {
+                on[idx] = false;
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    let buf = on
+        .iter()
+        .flat_map(|on| if *on { [255u8, 255u8] } else { [0u8, 0u8] })
+        .collect();
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Skeleton buffer should be sized correctly")
+}
@@ -0,0 +1,147 @@
+use image::RgbaImage;
+use rayon::prelude::*;
+use visusta_core::mosaic::{ColourFeatures, MosaicData, TileGrid, TileMatching};
+
+struct BlockMatches {
+    blocks_x: usize,
+    blocks_y: usize,
+    tiles: Vec<Option<usize>>,
+}
+
+fn match_blocks(img: &RgbaImage, data: &MosaicData) -> BlockMatches {
+    let block = data.block_size;
+    let blocks_x = img.width().div_ceil(block) as usize;
+    let blocks_y = img.height().div_ceil(block) as usize;
+
+    let tiles = &data.tiles.tiles;
+
+    // Every tile ranked best first, None for blocks with nothing visible
+    let ranked: Vec<Option<Vec<usize>>> = (0..blocks_x * blocks_y)
+        .into_par_iter()
+        .map(|block_idx| {
+            let x_start = (block_idx % blocks_x) as u32 * block;
+            let y_start = (block_idx / blocks_x) as u32 * block;
+
+            let pixels = (y_start..(y_start + block).min(img.height())).flat_map(|y| {
+                (x_start..(x_start + block).min(img.width())).map(move |x| img.get_pixel(x, y).0)
+            });
+
+            let features = ColourFeatures::from_pixels(pixels);
+
+            if features.histogram.iter().all(|val| *val == 0.0) {
+                return None;
+            }
+
+            let mut scored: Vec<(usize, f32)> = tiles
+                .iter()
+                .enumerate()
+                .map(|(idx, tile)| {
+                    let distance = match data.matching {
+                        TileMatching::MeanColour => features
+                            .mean_lab
+                            .iter()
+                            .zip(tile.mean_lab.iter())
+                            .map(|(a, b)| (a - b) * (a - b))
+                            .sum(),
+                        TileMatching::Histogram => {
+                            1.0 - features
+                                .histogram
+                                .iter()
+                                .zip(tile.histogram.iter())
+                                .map(|(a, b)| a.min(*b))
+                                .sum::<f32>()
+                        }
+                    };
+                    (idx, distance)
+                })
+                .collect();
+
+            scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            Some(scored.into_iter().map(|(idx, _)| idx).collect())
+        })
+        .collect();
+
+    let mut uses = vec![0u32; tiles.len()];
+
+    // Placement is sequential so repeat limits fill in reading order
+    let tiles = ranked
+        .into_iter()
+        .map(|ranking| {
+            let ranking = ranking?;
+
+            let chosen = match data.max_repeats {
+                Some(max) => ranking
+                    .iter()
+                    .copied()
+                    .find(|idx| uses[*idx] < max)
+                    .unwrap_or(ranking[0]),
+                None => ranking[0],
+            };
+
+            uses[chosen] += 1;
+            Some(chosen)
+        })
+        .collect();
+
+    BlockMatches {
+        blocks_x,
+        blocks_y,
+        tiles,
+    }
+}
+
+// Output is at tile resolution, each block becomes one full tile
+pub fn mosaic_on_rgba(img: &RgbaImage, data: &MosaicData) -> RgbaImage {
+    composite(&match_blocks(img, data), data)
+}
+
+pub fn mosaic_with_grid(img: &RgbaImage, data: &MosaicData) -> (RgbaImage, TileGrid) {
+    let matches = match_blocks(img, data);
+
+    let grid = TileGrid {
+        width: matches.blocks_x,
+        height: matches.blocks_y,
+        tiles: matches
+            .tiles
+            .iter()
+            .map(|idx| idx.map(|idx| data.tiles.tiles[idx].name.clone()))
+            .collect(),
+    };
+
+    (composite(&matches, data), grid)
+}
+
+fn composite(matches: &BlockMatches, data: &MosaicData) -> RgbaImage {
+    let tile_w = data.tiles.tile_width as usize;
+    let tile_h = data.tiles.tile_height as usize;
+
+    let width = matches.blocks_x * tile_w;
+    let height = matches.blocks_y * tile_h;
+
+    let mut buf = vec![0u8; width * height * 4];
+
+    buf.par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let block_y = y / tile_h;
+            let tile_y = (y % tile_h) as u32;
+
+            for block_x in 0..matches.blocks_x {
+                let Some(tile_idx) = matches.tiles[block_y * matches.blocks_x + block_x] else {
+                    continue;
+                };
+
+                let tile = &data.tiles.tiles[tile_idx].image;
+
+                for tile_x in 0..tile_w {
+                    let out_idx = (block_x * tile_w + tile_x) * 4;
+                    row[out_idx..out_idx + 4]
+                        .copy_from_slice(&tile.get_pixel(tile_x as u32, tile_y).0);
+                }
+            }
+        });
+
+    RgbaImage::from_raw(width as u32, height as u32, buf)
+        .expect("Mosaic buffer should be sized correctly")
+}
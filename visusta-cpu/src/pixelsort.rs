@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::{
+    gradient::GradientOperator,
+    pixelsort::{IntervalMode, PixelSortData, SortDirection, SortOrder},
+    random::SplitMix64,
+};
+
+use crate::convolve::{Plane, gradients};
+
+// Largest Sobel magnitude a 0 - 255 plane can produce
+const MAX_SOBEL: f32 = 4.0 * 255.0 * std::f32::consts::SQRT_2;
+
+pub fn pixel_sort_on_rgba(img: &RgbaImage, data: PixelSortData) -> RgbaImage {
+    let pixels: Vec<[u8; 4]> = img.pixels().map(|pixel| pixel.0).collect();
+
+    let sortable: Vec<bool> = match data.intervals {
+        IntervalMode::Threshold { key, low, high } => pixels
+            .iter()
+            .map(|pixel| {
+                let val = key.value(*pixel);
+                val >= low && val <= high
+            })
+            .collect(),
+        IntervalMode::Edges { threshold } => {
+            let (gx, gy) = gradients(
+                &Plane::from_rgba_luminance(img),
+                GradientOperator::Sobel3,
+                data.border,
+            );
+            gx.data
+                .iter()
+                .zip(gy.data.iter())
+                .map(|(gx, gy)| (gx * gx + gy * gy).sqrt() / MAX_SOBEL <= threshold)
+                .collect()
+        }
+    };
+
+    let lines = scan_lines(img.width(), img.height(), data.direction);
+
+    let moved: Vec<Vec<(usize, [u8; 4])>> = lines
+        .par_iter()
+        .enumerate()
+        .map(|(line_idx, line)| {
+            let mut rng = data.random.map(|random| {
+                SplitMix64::new(random.seed ^ (line_idx as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            });
+
+            let mut out = Vec::new();
+            let mut start = 0;
+
+            while start < line.len() {
+                if !sortable[line[start]] {
+                    start += 1;
+                    continue;
+                }
+
+                let mut end = start;
+                while end < line.len() && sortable[line[end]] {
+                    end += 1;
+                }
+
+                let mut piece_start = start;
+                while piece_start < end {
+                    let piece_end = match (data.random, rng.as_mut()) {
+                        (Some(random), Some(rng)) => {
+                            let length = rng.range(random.max_length as u64) as usize + 1;
+                            (piece_start + length).min(end)
+                        }
+                        _ => end,
+                    };
+
+                    let positions = &line[piece_start..piece_end];
+                    let mut sorted: Vec<(f32, [u8; 4])> = positions
+                        .iter()
+                        .map(|idx| (data.key.value(pixels[*idx]), pixels[*idx]))
+                        .collect();
+
+                    // Stable so equal keys keep their original order along the line
+                    sorted.sort_by(|a, b| match data.order {
+                        SortOrder::Ascending => a.0.total_cmp(&b.0),
+                        SortOrder::Descending => b.0.total_cmp(&a.0),
+                    });
+
+                    out.extend(
+                        positions
+                            .iter()
+                            .zip(sorted)
+                            .map(|(idx, (_, pixel))| (*idx, pixel)),
+                    );
+
+                    piece_start = piece_end;
+                }
+
+                start = end;
+            }
+
+            out
+        })
+        .collect();
+
+    let mut buf = img.as_raw().clone();
+    for (idx, pixel) in moved.into_iter().flatten() {
+        buf[idx * 4..idx * 4 + 4].copy_from_slice(&pixel);
+    }
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Pixel sort buffer should be sized correctly")
+}
+
+// Every pixel lands on exactly one line, ordered along the sort direction
+fn scan_lines(width: u32, height: u32, direction: SortDirection) -> Vec<Vec<usize>> {
+    let (width, height) = (width as usize, height as usize);
+
+    match direction {
+        SortDirection::Rows => (0..height)
+            .map(|y| (0..width).map(|x| y * width + x).collect())
+            .collect(),
+        SortDirection::Columns => (0..width)
+            .map(|x| (0..height).map(|y| y * width + x).collect())
+            .collect(),
+        SortDirection::Angle(angle) => {
+            let radians = angle.to_radians();
+            let (cos, sin) = (radians.cos(), radians.sin());
+
+            let mut lines: BTreeMap<i64, Vec<(f32, usize)>> = BTreeMap::new();
+
+            for y in 0..height {
+                for x in 0..width {
+                    let (px, py) = (x as f32, y as f32);
+                    let across = (-px * sin + py * cos).round() as i64;
+                    let along = px * cos + py * sin;
+                    lines
+                        .entry(across)
+                        .or_default()
+                        .push((along, y * width + x));
+                }
+            }
+
+            lines
+                .into_values()
+                .map(|mut line| {
+                    line.sort_by(|a, b| a.0.total_cmp(&b.0));
+                    line.into_iter().map(|(_, idx)| idx).collect()
+                })
+                .collect()
+        }
+    }
+}
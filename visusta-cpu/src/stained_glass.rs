@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use image::RgbaImage;
+use rayon::prelude::*;
+use visusta_core::{random::SplitMix64, voronoi::StainedGlassData};
+
+use crate::voronoi::jump_flood;
+
+pub fn stained_glass_on_rgba(img: &RgbaImage, data: StainedGlassData) -> RgbaImage {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let mut rng = SplitMix64::new(data.seed);
+    let seeds: Vec<(f32, f32)> = (0..data.cell_count)
+        .map(|_| {
+            (
+                rng.next_f32() * width as f32,
+                rng.next_f32() * height as f32,
+            )
+        })
+        .collect();
+
+    let labels = jump_flood(width, height, &seeds);
+
+    let mut sums = vec![[0u64; 5]; seeds.len()];
+    for (pixel, label) in img.pixels().zip(labels.iter()) {
+        let sum = &mut sums[*label as usize];
+        for (acc, val) in sum.iter_mut().zip(pixel.0) {
+            *acc += val as u64;
+        }
+        sum[4] += 1;
+    }
+
+    let colours: Vec<[u8; 4]> = sums
+        .into_iter()
+        .map(|sum| {
+            if sum[4] == 0 {
+                return [0, 0, 0, 0];
+            }
+            std::array::from_fn(|channel| (sum[channel] / sum[4]) as u8)
+        })
+        .collect();
+
+    let half_lead = data.lead_width / 2.0;
+    let window = (half_lead.ceil() as isize) + 1;
+
+    let mut buf = vec![0u8; width * height * 4];
+
+    buf.par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            let mut neighbours = HashSet::new();
+
+            for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                let label = labels[y * width + x];
+                let colour = colours[label as usize];
+
+                if data.lead_width <= 0.0 {
+                    pixel.copy_from_slice(&colour);
+                    continue;
+                }
+
+                neighbours.clear();
+                for dy in -window..=window {
+                    for dx in -window..=window {
+                        let nx = (x as isize + dx).clamp(0, width as isize - 1) as usize;
+                        let ny = (y as isize + dy).clamp(0, height as isize - 1) as usize;
+                        let other = labels[ny * width + nx];
+                        if other != label {
+                            neighbours.insert(other);
+                        }
+                    }
+                }
+
+                // Exact distance to the bisector with each neighbouring cell
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let (ax, ay) = seeds[label as usize];
+                let border_dist = neighbours
+                    .iter()
+                    .map(|other| {
+                        let (bx, by) = seeds[*other as usize];
+                        let to_a = (px - ax).powi(2) + (py - ay).powi(2);
+                        let to_b = (px - bx).powi(2) + (py - by).powi(2);
+                        let between = ((bx - ax).powi(2) + (by - ay).powi(2)).sqrt();
+                        (to_b - to_a) / (2.0 * between.max(f32::EPSILON))
+                    })
+                    .fold(f32::INFINITY, f32::min);
+
+                // One pixel of falloff keeps the lead anti-aliased
+                let lead = (half_lead - border_dist + 0.5).clamp(0.0, 1.0);
+
+                for ((out, cell), lead_val) in pixel.iter_mut().zip(colour).zip(data.lead_colour) {
+                    *out = (cell as f32 + (lead_val as f32 - cell as f32) * lead).round() as u8;
+                }
+            }
+        });
+
+    RgbaImage::from_raw(img.width(), img.height(), buf)
+        .expect("Stained glass buffer should be sized correctly")
+}
@@ -0,0 +1,118 @@
+use image::RgbaImage;
+use visusta_core::{
+    random::SplitMix64,
+    voronoi::{StippleData, StippleSet},
+};
+
+use crate::voronoi::jump_flood;
+
+// Secord's weighted Voronoi stippling, darker areas pull more dots towards them
+pub fn stipple_points(img: &RgbaImage, data: StippleData) -> StippleSet {
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let density: Vec<f32> = img
+        .pixels()
+        .map(|pixel| {
+            let luminance =
+                0.299 * pixel.0[0] as f32 + 0.587 * pixel.0[1] as f32 + 0.114 * pixel.0[2] as f32;
+            (1.0 - luminance / 255.0) * pixel.0[3] as f32 / 255.0
+        })
+        .collect();
+
+    let mut cumulative = Vec::with_capacity(density.len());
+    let mut total = 0f32;
+    for val in density.iter() {
+        // A small floor keeps white images from having nowhere to put the dots
+        total += val + 1e-4;
+        cumulative.push(total);
+    }
+
+    let mut rng = SplitMix64::new(data.seed);
+
+    let mut points: Vec<(f32, f32)> = (0..data.dot_count)
+        .map(|_| {
+            let target = rng.next_f32() * total;
+            let idx = cumulative
+                .partition_point(|val| *val < target)
+                .min(cumulative.len() - 1);
+
+            // Jittered inside the pixel so dots landing on the same one can separate
+            (
+                (idx % width) as f32 + rng.next_f32(),
+                (idx / width) as f32 + rng.next_f32(),
+            )
+        })
+        .collect();
+
+    for _ in 0..data.iterations {
+        let labels = jump_flood(width, height, &points);
+
+        let mut sums = vec![[0f64; 3]; points.len()];
+        for (idx, (label, weight)) in labels.iter().zip(density.iter()).enumerate() {
+            let sum = &mut sums[*label as usize];
+            let weight = *weight as f64;
+            sum[0] += weight * ((idx % width) as f64 + 0.5);
+            sum[1] += weight * ((idx / width) as f64 + 0.5);
+            sum[2] += weight;
+        }
+
+        // Cells with no darkness at all keep their dot where it is
+        for (point, sum) in points.iter_mut().zip(sums) {
+            if sum[2] > 0.0 {
+                *point = ((sum[0] / sum[2]) as f32, (sum[1] / sum[2]) as f32);
+            }
+        }
+    }
+
+    StippleSet {
+        width: img.width(),
+        height: img.height(),
+        points,
+        radius: data.dot_radius,
+        dot_colour: data.dot_colour,
+        background: data.background,
+    }
+}
+
+pub fn stipple_on_rgba(img: &RgbaImage, data: StippleData) -> RgbaImage {
+    let set = stipple_points(img, data);
+
+    let width = img.width() as usize;
+    let height = img.height() as usize;
+
+    let mut coverage = vec![0f32; width * height];
+    let reach = set.radius + 0.5;
+
+    for (px, py) in set.points.iter() {
+        let min_x = (px - reach).floor().max(0.0) as usize;
+        let min_y = (py - reach).floor().max(0.0) as usize;
+        let max_x = ((px + reach).ceil().max(0.0) as usize).min(width);
+        let max_y = ((py + reach).ceil().max(0.0) as usize).min(height);
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let dist = ((x as f32 + 0.5 - px).powi(2) + (y as f32 + 0.5 - py).powi(2)).sqrt();
+
+                // One pixel of falloff at the rim keeps small dots round
+                let amount = (set.radius - dist + 0.5).clamp(0.0, 1.0);
+                let cell = &mut coverage[y * width + x];
+                *cell = cell.max(amount);
+            }
+        }
+    }
+
+    let buf = coverage
+        .iter()
+        .flat_map(|amount| {
+            let mut pixel = set.background;
+            for (out, dot) in pixel.iter_mut().zip(set.dot_colour) {
+                *out = (*out as f32 + (dot as f32 - *out as f32) * amount).round() as u8;
+            }
+            pixel
+        })
+        .collect();
+
+    RgbaImage::from_raw(img.width(), img.height(), buf)
+        .expect("Stipple buffer should be sized correctly")
+}
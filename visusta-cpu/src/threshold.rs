@@ -0,0 +1,109 @@
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::{
+    LumaAImage,
+    gaussians::gaussian_kernel_1d,
+    threshold::{
+        AdaptiveMethod, AdaptiveThreshold, PosterizeData, ThresholdFilter, posterize_value,
+    },
+};
+
+use crate::{
+    convolve::{Plane, separable_blur},
+    histogram::{luma_histogram, otsu_threshold},
+};
+
+// Binary results follow the edge maps, 255,255 on and 0,0 off
+pub fn threshold_on_luma(img: &LumaAImage, filter: ThresholdFilter) -> LumaAImage {
+    match filter {
+        ThresholdFilter::Fixed(cutoff) => binary(img, |_, val| val > cutoff as f32),
+        ThresholdFilter::Otsu => {
+            let cutoff = otsu_threshold(&luma_histogram(img));
+            binary(img, |_, val| val > cutoff as f32)
+        }
+        ThresholdFilter::Adaptive(data) => {
+            let local = local_mean(img, data);
+            binary(img, |idx, val| val > local.data[idx] - data.offset)
+        }
+        ThresholdFilter::Posterize(levels) => {
+            let buf = img
+                .pixels()
+                .flat_map(|pixel| [posterize_value(pixel.0[0], levels), pixel.0[1]])
+                .collect();
+
+            ImageBuffer::from_raw(img.width(), img.height(), buf)
+                .expect("Posterize buffer should be sized correctly")
+        }
+    }
+}
+
+pub fn posterize_on_rgba(img: &RgbaImage, data: PosterizeData) -> RgbaImage {
+    let width = img.width() as usize;
+    let levels = [data.r, data.g, data.b];
+
+    let mut buf = img.as_raw().clone();
+
+    buf.par_chunks_mut(width * 4).for_each(|row| {
+        for pixel in row.chunks_exact_mut(4) {
+            for (val, levels) in pixel.iter_mut().zip(levels) {
+                *val = posterize_value(*val, levels);
+            }
+        }
+    });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Posterize buffer should be sized correctly")
+}
+
+// Otsu over float values, binned across their own range
+pub fn otsu_cutoff(values: &[f32]) -> f32 {
+    let (min, max) = values
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), val| {
+            (min.min(*val), max.max(*val))
+        });
+
+    if values.is_empty() || max <= min {
+        return min;
+    }
+
+    let scale = 255.0 / (max - min);
+
+    let mut hist = [0u32; 256];
+    for val in values {
+        hist[((val - min) * scale) as usize] += 1;
+    }
+
+    // Upper edge of the chosen bin so values inside it stay below the cutoff
+    min + (otsu_threshold(&hist) as f32 + 1.0) / scale
+}
+
+fn local_mean(img: &LumaAImage, data: AdaptiveThreshold) -> Plane {
+    let kernel = match data.method {
+        AdaptiveMethod::Mean => vec![1.0 / data.block_size as f32; data.block_size as usize],
+        // Radius ceil(3 sigma) lands on half the block
+        AdaptiveMethod::Gaussian => gaussian_kernel_1d(data.block_size as f32 / 6.0),
+    };
+
+    separable_blur(&Plane::from_luma(img), &kernel, data.border)
+}
+
+fn binary(img: &LumaAImage, on: impl Fn(usize, f32) -> bool + Sync) -> LumaAImage {
+    let width = img.width() as usize;
+
+    let mut buf = vec![0u8; width * img.height() as usize * 2];
+
+    buf.par_chunks_mut(width * 2)
+        .enumerate()
+        .for_each(|(y, row)| {
+            for (x, pixel) in row.chunks_exact_mut(2).enumerate() {
+                let source = img.get_pixel(x as u32, y as u32).0;
+                if source[1] > 0 && on(y * width + x, source[0] as f32) {
+                    pixel.copy_from_slice(&[255, 255]);
+                }
+            }
+        });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Threshold buffer should be sized correctly")
+}
@@ -0,0 +1,35 @@
+use image::{ImageBuffer, RgbaImage};
+use rayon::prelude::*;
+use visusta_core::{LumaAImage, tone::ToneLuts};
+
+pub fn tone_on_rgba(img: &RgbaImage, luts: &ToneLuts) -> RgbaImage {
+    let width = img.width() as usize;
+
+    let mut buf = img.as_raw().clone();
+
+    buf.par_chunks_mut(width * 4).for_each(|row| {
+        for pixel in row.chunks_exact_mut(4) {
+            pixel[0] = luts.r[pixel[0] as usize];
+            pixel[1] = luts.g[pixel[1] as usize];
+            pixel[2] = luts.b[pixel[2] as usize];
+        }
+    });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Tone buffer should be sized correctly")
+}
+
+pub fn tone_on_luma(img: &LumaAImage, luts: &ToneLuts) -> LumaAImage {
+    let width = img.width() as usize;
+
+    let mut buf = img.as_raw().clone();
+
+    buf.par_chunks_mut(width * 2).for_each(|row| {
+        for pixel in row.chunks_exact_mut(2) {
+            pixel[0] = luts.luma[pixel[0] as usize];
+        }
+    });
+
+    ImageBuffer::from_raw(img.width(), img.height(), buf)
+        .expect("Tone buffer should be sized correctly")
+}
@@ -0,0 +1,79 @@
+use rayon::prelude::*;
+
+// Nearest seed for every pixel centre, seeds are in pixel space with centres at +0.5
+pub fn jump_flood(width: usize, height: usize, seeds: &[(f32, f32)]) -> Vec<u32> {
+    assert!(!seeds.is_empty(), "Voronoi needs at least one seed");
+
+    let dist_sq = |x: usize, y: usize, seed: u32| {
+        let (sx, sy) = seeds[seed as usize];
+        let dx = x as f32 + 0.5 - sx;
+        let dy = y as f32 + 0.5 - sy;
+        dx * dx + dy * dy
+    };
+
+    let mut labels = vec![u32::MAX; width * height];
+
+    for (idx, (sx, sy)) in seeds.iter().enumerate() {
+        let x = (sx.max(0.0) as usize).min(width - 1);
+        let y = (sy.max(0.0) as usize).min(height - 1);
+        let slot = &mut labels[y * width + x];
+
+        // Seeds sharing a pixel keep the closer one, the other is picked up by the passes
+        if *slot == u32::MAX || dist_sq(x, y, idx as u32) < dist_sq(x, y, *slot) {
+            *slot = idx as u32;
+        }
+    }
+
+    let mut steps = Vec::new();
+    let mut step = width.max(height).next_power_of_two() / 2;
+    while step >= 1 {
+        steps.push(step);
+        step /= 2;
+    }
+    // A final extra pass of 1 fixes most of the errors plain JFA leaves behind
+    steps.push(1);
+
+    for step in steps {
+        let step = step as isize;
+        let prev = labels.clone();
+
+        labels
+            .par_chunks_mut(width)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, label) in row.iter_mut().enumerate() {
+                    let mut best = *label;
+                    let mut best_dist = if best == u32::MAX {
+                        f32::INFINITY
+                    } else {
+                        dist_sq(x, y, best)
+                    };
+
+                    for dy in [-step, 0, step] {
+                        for dx in [-step, 0, step] {
+                            let nx = x as isize + dx;
+                            let ny = y as isize + dy;
+                            if nx < 0 || ny < 0 || nx >= width as isize || ny >= height as isize {
+                                continue;
+                            }
+
+                            let candidate = prev[ny as usize * width + nx as usize];
+                            if candidate == u32::MAX || candidate == best {
+                                continue;
+                            }
+
+                            let dist = dist_sq(x, y, candidate);
+                            if dist < best_dist {
+                                best = candidate;
+                                best_dist = dist;
+                            }
+                        }
+                    }
+
+                    *label = best;
+                }
+            });
+    }
+
+    labels
+}
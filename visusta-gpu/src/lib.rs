@@ -1,10 +1,34 @@
 use async_trait::async_trait;
-use image::RgbaImage;
+use image::{Rgba32FImage, RgbaImage};
 use visusta_core::{
-    CharImage, LumaAImage, LuminanceAsciiFilter, LuminanceFilter, SobelAscii, SobelColorData,
-    VisustaProcessor,
-    gaussians::{GaussianBuilder, GaussianColorData},
+    CharImage, GradientAsciiData, GradientColourData, LumaA32FImage, LumaAImage,
+    LuminanceAsciiFilter, LuminanceFilter, SobelAscii, SobelColorData, VisustaProcessor,
+    auxiliary::{BlendData, SizePolicy},
+    border::BorderMode,
+    canny::{CannyData, CannyThresholds},
+    channel::ChannelMapping,
+    effects::{BloomData, GrainData, VignetteData},
+    float::FloatRange,
+    gaussians::DogOutput,
+    gaussians::GaussianBuilder,
+    grade::ColourGrade,
+    gradient::{GradientData, GradientField, GradientMagnitude},
+    gradient_map::GradientMap,
+    halftone::{CmykHalftoneData, HalftoneData},
+    hatch::HatchData,
+    histogram::HistogramFilter,
+    kuwahara::KuwaharaData,
+    lowpoly::{LowPolyData, TriangleMesh},
+    lut::LutData,
+    mask::{LuminanceMask, MaskData},
+    morphology::MorphologyOp,
+    mosaic::{MosaicData, TileGrid},
     pipeline::LayerOutput,
+    pixelsort::PixelSortData,
+    threshold::{PosterizeData, ThresholdFilter},
+    tone::ToneFilter,
+    voronoi::{StainedGlassData, StippleData, StippleSet},
+    xdog::XDogData,
 };
 use visusta_cpu::VisustaCPU;
 
@@ -22,8 +46,13 @@ impl VisustaProcessor for VisustaGPU {
         self.cpu.luma_to_rgba(img).await
     }
 
-    async fn sobel_to_colour(&self, img: &LumaAImage, filter: SobelColorData) -> RgbaImage {
-        self.cpu.sobel_to_colour(img, filter).await
+    async fn sobel_to_colour(
+        &self,
+        img: &LumaAImage,
+        filter: SobelColorData,
+        original: Option<&RgbaImage>,
+    ) -> RgbaImage {
+        self.cpu.sobel_to_colour(img, filter, original).await
     }
 
     async fn gaussian_on_luma(&self, img: &LumaAImage, builder: GaussianBuilder) -> LumaAImage {
@@ -34,9 +63,12 @@ impl VisustaProcessor for VisustaGPU {
         &self,
         img: &LumaAImage,
         builder: GaussianBuilder,
-        filter: GaussianColorData,
+        channels: ChannelMapping,
+        original: Option<&RgbaImage>,
     ) -> RgbaImage {
-        self.cpu.gaussian_to_coloured(img, builder, filter).await
+        self.cpu
+            .gaussian_to_coloured(img, builder, channels, original)
+            .await
     }
 
     async fn luminance_to_ascii(
@@ -51,6 +83,259 @@ impl VisustaProcessor for VisustaGPU {
         self.cpu.sobel_ascii_directional(img, filter).await
     }
 
+    async fn tone_rgba(&self, img: &RgbaImage, filter: ToneFilter) -> RgbaImage {
+        self.cpu.tone_rgba(img, filter).await
+    }
+
+    async fn tone_luma(&self, img: &LumaAImage, filter: ToneFilter) -> LumaAImage {
+        self.cpu.tone_luma(img, filter).await
+    }
+
+    async fn histogram_luma(&self, img: &LumaAImage, filter: HistogramFilter) -> LumaAImage {
+        self.cpu.histogram_luma(img, filter).await
+    }
+
+    async fn canny_on_luma(&self, img: &LumaAImage, data: CannyData) -> LumaAImage {
+        self.cpu.canny_on_luma(img, data).await
+    }
+
+    async fn blur_luma(&self, img: &LumaAImage, sigma: f32, border: BorderMode) -> LumaAImage {
+        self.cpu.blur_luma(img, sigma, border).await
+    }
+
+    async fn blur_rgba(&self, img: &RgbaImage, sigma: f32, border: BorderMode) -> RgbaImage {
+        self.cpu.blur_rgba(img, sigma, border).await
+    }
+
+    async fn dog_on_luma(
+        &self,
+        img: &LumaAImage,
+        builder: GaussianBuilder,
+        output: DogOutput,
+    ) -> LumaAImage {
+        self.cpu.dog_on_luma(img, builder, output).await
+    }
+
+    async fn xdog_on_luma(&self, img: &LumaAImage, data: XDogData) -> LumaAImage {
+        self.cpu.xdog_on_luma(img, data).await
+    }
+
+    async fn kuwahara_rgba(&self, img: &RgbaImage, data: KuwaharaData) -> RgbaImage {
+        self.cpu.kuwahara_rgba(img, data).await
+    }
+
+    async fn grain_rgba(&self, img: &RgbaImage, data: GrainData) -> RgbaImage {
+        self.cpu.grain_rgba(img, data).await
+    }
+
+    async fn vignette_rgba(&self, img: &RgbaImage, data: VignetteData) -> RgbaImage {
+        self.cpu.vignette_rgba(img, data).await
+    }
+
+    async fn bloom_rgba(&self, img: &RgbaImage, data: BloomData) -> RgbaImage {
+        self.cpu.bloom_rgba(img, data).await
+    }
+
+    async fn mosaic_rgba(&self, img: &RgbaImage, data: MosaicData) -> RgbaImage {
+        self.cpu.mosaic_rgba(img, data).await
+    }
+
+    async fn mosaic_rgba_grid(&self, img: &RgbaImage, data: MosaicData) -> (RgbaImage, TileGrid) {
+        self.cpu.mosaic_rgba_grid(img, data).await
+    }
+
+    async fn low_poly_rgba(&self, img: &RgbaImage, data: LowPolyData) -> RgbaImage {
+        self.cpu.low_poly_rgba(img, data).await
+    }
+
+    async fn low_poly_mesh(&self, img: &RgbaImage, data: LowPolyData) -> TriangleMesh {
+        self.cpu.low_poly_mesh(img, data).await
+    }
+
+    async fn stained_glass_rgba(&self, img: &RgbaImage, data: StainedGlassData) -> RgbaImage {
+        self.cpu.stained_glass_rgba(img, data).await
+    }
+
+    async fn stipple_rgba(&self, img: &RgbaImage, data: StippleData) -> RgbaImage {
+        self.cpu.stipple_rgba(img, data).await
+    }
+
+    async fn stipple_points(&self, img: &RgbaImage, data: StippleData) -> StippleSet {
+        self.cpu.stipple_points(img, data).await
+    }
+
+    async fn halftone_rgba(&self, img: &RgbaImage, data: HalftoneData) -> LumaAImage {
+        self.cpu.halftone_rgba(img, data).await
+    }
+
+    async fn halftone_cmyk(&self, img: &RgbaImage, data: CmykHalftoneData) -> RgbaImage {
+        self.cpu.halftone_cmyk(img, data).await
+    }
+
+    async fn pixel_sort_rgba(&self, img: &RgbaImage, data: PixelSortData) -> RgbaImage {
+        self.cpu.pixel_sort_rgba(img, data).await
+    }
+
+    async fn hatch_rgba(
+        &self,
+        img: &RgbaImage,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage {
+        self.cpu.hatch_rgba(img, data, paper).await
+    }
+
+    async fn hatch_luma(
+        &self,
+        img: &LumaAImage,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage {
+        self.cpu.hatch_luma(img, data, paper).await
+    }
+
+    async fn threshold_luma(&self, img: &LumaAImage, filter: ThresholdFilter) -> LumaAImage {
+        self.cpu.threshold_luma(img, filter).await
+    }
+
+    async fn posterize_rgba(&self, img: &RgbaImage, data: PosterizeData) -> RgbaImage {
+        self.cpu.posterize_rgba(img, data).await
+    }
+
+    async fn morphology_luma(&self, img: &LumaAImage, op: MorphologyOp) -> LumaAImage {
+        self.cpu.morphology_luma(img, op).await
+    }
+
+    async fn gradient_luma(&self, img: &LumaAImage, data: GradientData) -> GradientField {
+        self.cpu.gradient_luma(img, data).await
+    }
+
+    async fn gradient_to_colour(
+        &self,
+        field: &GradientField,
+        filter: GradientColourData,
+        original: Option<&RgbaImage>,
+    ) -> RgbaImage {
+        self.cpu.gradient_to_colour(field, filter, original).await
+    }
+
+    async fn gradient_to_luma(
+        &self,
+        field: &GradientField,
+        measure: GradientMagnitude,
+    ) -> LumaAImage {
+        self.cpu.gradient_to_luma(field, measure).await
+    }
+
+    async fn gradient_hatch(
+        &self,
+        field: &GradientField,
+        data: HatchData,
+        paper: Option<&RgbaImage>,
+    ) -> LumaAImage {
+        self.cpu.gradient_hatch(field, data, paper).await
+    }
+
+    async fn gradient_edges(
+        &self,
+        field: &GradientField,
+        thresholds: CannyThresholds,
+    ) -> LumaAImage {
+        self.cpu.gradient_edges(field, thresholds).await
+    }
+
+    async fn gradient_to_ascii(
+        &self,
+        field: &GradientField,
+        filter: GradientAsciiData,
+    ) -> CharImage {
+        self.cpu.gradient_to_ascii(field, filter).await
+    }
+
+    async fn rgba_to_float(&self, img: &RgbaImage) -> Rgba32FImage {
+        self.cpu.rgba_to_float(img).await
+    }
+
+    async fn rgba_from_float(&self, img: &Rgba32FImage, range: FloatRange) -> RgbaImage {
+        self.cpu.rgba_from_float(img, range).await
+    }
+
+    async fn luma_to_float(&self, img: &LumaAImage) -> LumaA32FImage {
+        self.cpu.luma_to_float(img).await
+    }
+
+    async fn luma_from_float(&self, img: &LumaA32FImage, range: FloatRange) -> LumaAImage {
+        self.cpu.luma_from_float(img, range).await
+    }
+
+    async fn rgba_to_luma_f32(&self, img: &Rgba32FImage, filter: LuminanceFilter) -> LumaA32FImage {
+        self.cpu.rgba_to_luma_f32(img, filter).await
+    }
+
+    async fn blur_rgba_f32(
+        &self,
+        img: &Rgba32FImage,
+        sigma: f32,
+        border: BorderMode,
+    ) -> Rgba32FImage {
+        self.cpu.blur_rgba_f32(img, sigma, border).await
+    }
+
+    async fn blur_luma_f32(
+        &self,
+        img: &LumaA32FImage,
+        sigma: f32,
+        border: BorderMode,
+    ) -> LumaA32FImage {
+        self.cpu.blur_luma_f32(img, sigma, border).await
+    }
+
+    async fn dog_on_luma_f32(
+        &self,
+        img: &LumaA32FImage,
+        builder: GaussianBuilder,
+    ) -> LumaA32FImage {
+        self.cpu.dog_on_luma_f32(img, builder).await
+    }
+
+    async fn gradient_luma_f32(&self, img: &LumaA32FImage, data: GradientData) -> GradientField {
+        self.cpu.gradient_luma_f32(img, data).await
+    }
+
+    async fn luminance_mask(&self, img: &LumaAImage, data: LuminanceMask) -> LumaAImage {
+        self.cpu.luminance_mask(img, data).await
+    }
+
+    async fn apply_mask(&self, img: &RgbaImage, mask: &LumaAImage, data: MaskData) -> RgbaImage {
+        self.cpu.apply_mask(img, mask, data).await
+    }
+
+    async fn fit_rgba(
+        &self,
+        img: &RgbaImage,
+        width: u32,
+        height: u32,
+        fit: SizePolicy,
+    ) -> RgbaImage {
+        self.cpu.fit_rgba(img, width, height, fit).await
+    }
+
+    async fn blend_rgba(&self, img: &RgbaImage, top: &RgbaImage, data: BlendData) -> RgbaImage {
+        self.cpu.blend_rgba(img, top, data).await
+    }
+
+    async fn lut_rgba(&self, img: &RgbaImage, data: LutData) -> RgbaImage {
+        self.cpu.lut_rgba(img, data).await
+    }
+
+    async fn grade_rgba(&self, img: &RgbaImage, grade: ColourGrade) -> RgbaImage {
+        self.cpu.grade_rgba(img, grade).await
+    }
+
+    async fn gradient_map(&self, img: &LumaAImage, map: GradientMap) -> RgbaImage {
+        self.cpu.gradient_map(img, map).await
+    }
+
     async fn overlay_layers(&self, layers: &[LayerOutput]) -> Option<LayerOutput> {
         self.cpu.overlay_layers(layers).await
     }
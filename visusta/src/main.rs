@@ -2,10 +2,15 @@ use std::path::Path;
 
 use image::{DynamicImage, RgbaImage, imageops::FilterType};
 use visusta_core::{
-    LuminanceAsciiFilter, LuminanceFilter, SobelAscii, SobelColorData, SobelColorItem,
+    LuminanceAsciiFilter, LuminanceFilter, SobelAscii, SobelColorData, SobelColouring,
     VisustaProcessor,
-    gaussians::{GaussianBuilder, GaussianColorData, GaussianColorItem},
+    border::BorderMode,
+    channel::{ChannelMap, ChannelMapping, ChannelSource},
+    float::FloatRange,
+    gaussians::GaussianBuilder,
+    gradient::{GradientMagnitude, GradientOperator},
     pipeline::{Layer, LayerOutput, Pipeline, ProcessingStep},
+    threshold::Cutoff,
 };
 use visusta_cpu::VisustaCPU;
 use visusta_gpu::VisustaGPU;
@@ -110,12 +115,13 @@ fn _create_main_pipeline() -> Pipeline {
         ))
         .add_step(ProcessingStep::GaussianToColoured(
             GaussianBuilder::create(0.5, 2.25).scalar(0.5).cutoff(40.0),
-            GaussianColorData {
-                r: GaussianColorItem::Absolute(255),
-                g: GaussianColorItem::NormalScale(2.0),
-                b: GaussianColorItem::NormalScale(2.0),
-                a: GaussianColorItem::Absolute(255),
+            ChannelMapping {
+                r: ChannelMap::constant(255),
+                g: ChannelMap::create(ChannelSource::Response).scale(2.0),
+                b: ChannelMap::create(ChannelSource::Response).scale(2.0),
+                a: ChannelMap::constant(255),
             },
+            None,
         ));
 
     let foreground = Layer::new()
@@ -125,13 +131,16 @@ fn _create_main_pipeline() -> Pipeline {
         .add_step(ProcessingStep::GaussianOnLuma(
             GaussianBuilder::create(0.5, 2.25).scalar(0.5).cutoff(40.0),
         ))
-        .add_step(ProcessingStep::SobelToColour(SobelColorData {
-            magnitude_min: 24,
-            r: SobelColorItem::Absolute(7),
-            g: SobelColorItem::Absolute(98),
-            b: SobelColorItem::Absolute(180),
-            a: SobelColorItem::Absolute(255),
-        }));
+        .add_step(ProcessingStep::SobelToColour(
+            SobelColorData {
+                operator: GradientOperator::Sobel3,
+                magnitude: GradientMagnitude::Squared,
+                magnitude_min: Cutoff::Value(24.0),
+                border: BorderMode::Clamp,
+                colouring: SobelColouring::Channels(ChannelMapping::solid(7, 98, 180, 255)),
+            },
+            None,
+        ));
 
     Pipeline::new()
         .add_layer(background)
@@ -154,7 +163,7 @@ async fn run_pipeline_process(
     let result = processor
         .overlay_layers(&outputs)
         .await
-        .ok_or_else(|| anyhow::anyhow!("No layers to composite or type mismatch"))?;
+        .ok_or_else(|| anyhow::anyhow!("No layers to composite, or their types or sizes differ"))?;
 
     match result {
         LayerOutput::Rgba(rgba) => {
@@ -172,6 +181,28 @@ async fn run_pipeline_process(
                 .save("./pipeline_output.png")?;
             println!("Pipeline output saved to ./pipeline_output.png");
         }
+        LayerOutput::Rgba32F(rgba) => {
+            DynamicImage::from(rgba)
+                .to_rgba8()
+                .save("./pipeline_output.png")?;
+            println!("Pipeline output saved to ./pipeline_output.png");
+        }
+        LayerOutput::Luma32F(luma) => {
+            let luma = processor.luma_from_float(&luma, FloatRange::Clamp).await;
+            DynamicImage::from(luma)
+                .to_rgba8()
+                .save("./pipeline_output.png")?;
+            println!("Pipeline output saved to ./pipeline_output.png");
+        }
+        LayerOutput::Gradient(field) => {
+            let luma = processor
+                .gradient_to_luma(&field, GradientMagnitude::Linear)
+                .await;
+            DynamicImage::from(luma)
+                .to_rgba8()
+                .save("./pipeline_output.png")?;
+            println!("Pipeline output saved to ./pipeline_output.png");
+        }
     }
 
     Ok(())